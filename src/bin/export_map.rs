@@ -0,0 +1,109 @@
+//! Generates a world without opening the game and writes it out as a
+//! colour-coded PNG and/or an ASCII grid.
+//!
+//! `cargo run --bin export_map -- --seed 42 --width 128 --height 64 --png map.png`
+
+use std::{env, fs, process};
+
+use game_growth::{global_state::GlobalState, map_export, world_gen};
+
+const USAGE: &str = "Usage: export_map [options]
+
+Options:
+    --seed <number>         World seed, random when left out
+    --width <tiles>         Number of columns (default 64)
+    --height <tiles>        Number of rows (default 32)
+    --resources <amount>    scarce, normal or abundant
+    --water <level>         low, normal or high
+    --png <path>            Write a colour-coded PNG of the map
+    --scale <pixels>        Pixels per tile in the PNG (default 4)
+    --ascii <path>          Write an ASCII map, `-` prints it
+
+Prints the ASCII map when neither --png nor --ascii is given.";
+
+struct ExportOptions {
+    global_state: GlobalState,
+    png: Option<String>,
+    scale: u32,
+    ascii: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<ExportOptions, String> {
+    let mut options = ExportOptions {
+        global_state: GlobalState::default(),
+        png: None,
+        scale: 4,
+        ascii: None,
+    };
+
+    while let Some(flag) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{} is missing a value", flag))
+        };
+
+        match flag.as_str() {
+            "--seed" => options.global_state.seed = parse_number(&flag, value()?)?,
+            "--width" => options.global_state.world_cols = parse_number(&flag, value()?)?,
+            "--height" => options.global_state.world_rows = parse_number(&flag, value()?)?,
+            "--resources" => options.global_state.resource_abundance = value()?.parse()?,
+            "--water" => options.global_state.water_level = value()?.parse()?,
+            "--png" => options.png = Some(value()?),
+            "--scale" => options.scale = parse_number(&flag, value()?)?,
+            "--ascii" => options.ascii = Some(value()?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+
+    if options.global_state.world_cols == 0 || options.global_state.world_rows == 0 {
+        return Err("the map needs at least one column and one row".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got {}", flag, value))
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(1);
+    });
+
+    let map = world_gen::generate_map(&options.global_state);
+    eprintln!(
+        "Generated a {}x{} map with seed {}",
+        map.cols, map.rows, options.global_state.seed
+    );
+
+    if let Some(path) = &options.png {
+        if let Err(error) = map_export::render_png(&map, options.scale).save(path) {
+            eprintln!("Could not write {}: {}", path, error);
+            process::exit(1);
+        }
+    }
+
+    let ascii_path = match (&options.png, &options.ascii) {
+        (None, None) => Some("-"),
+        (_, ascii) => ascii.as_deref(),
+    };
+
+    match ascii_path {
+        Some("-") => print!("{}", map_export::render_ascii(&map)),
+        Some(path) => {
+            if let Err(error) = fs::write(path, map_export::render_ascii(&map)) {
+                eprintln!("Could not write {}: {}", path, error);
+                process::exit(1);
+            }
+        }
+        None => {}
+    }
+}
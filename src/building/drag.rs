@@ -0,0 +1,177 @@
+//! Dragging out a rectangle or line of repeatable buildings like houses and
+//! farms. Every building is previewed while dragging, and the ones that fit
+//! and can be paid for are placed when the mouse is let go.
+
+use bevy::prelude::*;
+
+use super::{
+    BuildingIndicator, BuildingState, BuildingType, Facing, PlaceBuilding, RoadNetwork, Territory,
+};
+use crate::{economy::Stockpile, loading::TextureAssets, ui::PanelState, world_gen::WorldMap};
+
+/// The area being dragged out and the buildings in it, with whether each of
+/// them will be placed
+#[derive(Resource, Default)]
+pub(super) struct PlacementDrag {
+    start: Option<IVec2>,
+    buildings: Vec<(IVec2, Facing, bool)>,
+}
+
+/// Selector shown on each tile of the dragged area
+#[derive(Component)]
+pub(super) struct PlacementPreview;
+
+impl BuildingType {
+    /// Can be dragged out over an area, several at a time
+    pub fn is_repeatable(&self) -> bool {
+        matches!(
+            self,
+            BuildingType::Farm
+                | BuildingType::HouseOne
+                | BuildingType::HouseTwo
+                | BuildingType::House
+                | BuildingType::Shade
+        )
+    }
+}
+
+impl PlacementDrag {
+    /// Where each building goes when filling the rectangle from `start` to
+    /// `end`, or only its longer side for a line, with buildings of `size`
+    /// tiles. Closest to `start` first, so those are the ones paid for when
+    /// the stockpile runs short.
+    fn area(start: IVec2, end: IVec2, line: bool, size: IVec2) -> Vec<IVec2> {
+        let delta = end - start;
+        let delta = match line {
+            true if delta.x.abs() >= delta.y.abs() => IVec2::new(delta.x, 0),
+            true => IVec2::new(0, delta.y),
+            false => delta,
+        };
+        let count = delta.abs() / size;
+        let step = delta.signum() * size;
+
+        let mut anchors = (0..=count.x)
+            .flat_map(|x| (0..=count.y).map(move |y| start + step * IVec2::new(x, y)))
+            .collect::<Vec<_>>();
+        anchors.sort_by_key(|anchor| {
+            let offset = (*anchor - start).abs();
+            offset.x + offset.y
+        });
+
+        anchors
+    }
+
+    /// Starts a drag on mouse down, works out which tiles of the area can
+    /// take the building while it is held and places them on mouse up.
+    /// Holding Shift drags a line instead of a rectangle.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn track(
+        buttons: Res<Input<MouseButton>>,
+        keys: Res<Input<KeyCode>>,
+        state: Res<BuildingState>,
+        panel_state: Res<PanelState>,
+        indicator_query: Query<&BuildingIndicator>,
+        map: Option<Res<WorldMap>>,
+        territory: Res<Territory>,
+        network: Res<RoadNetwork>,
+        stockpile: Res<Stockpile>,
+        mut drag: ResMut<PlacementDrag>,
+        mut event: EventWriter<PlaceBuilding>,
+    ) {
+        let building = state.building.filter(|building| {
+            state.mode_active && !panel_state.active && building.is_repeatable()
+        });
+
+        let (Some(building), Some(map), Some(tile)) =
+            (building, map, indicator_query.single().tile)
+        else {
+            if drag.start.is_some() {
+                *drag = PlacementDrag::default();
+            }
+            return;
+        };
+
+        if buttons.just_pressed(MouseButton::Left) {
+            drag.start = Some(tile);
+        }
+        let Some(start) = drag.start else {
+            return;
+        };
+
+        let line = keys.pressed(KeyCode::LShift) || keys.pressed(KeyCode::RShift);
+        let mut budget = stockpile.clone();
+        let buildings = PlacementDrag::area(start, tile, line, building.size(state.facing))
+            .into_iter()
+            .map(|anchor| {
+                let facing = building.facing_for_access(&network, anchor, state.facing);
+                let fits = building
+                    .check_placement(&map, &territory, anchor, facing)
+                    .is_ok();
+                (anchor, facing, fits && budget.spend(building.cost()))
+            })
+            .collect::<Vec<_>>();
+
+        if drag.buildings != buildings {
+            drag.buildings = buildings;
+        }
+
+        if !buttons.pressed(MouseButton::Left) {
+            for (anchor, facing, valid) in drag.buildings.iter() {
+                if *valid {
+                    event.send(PlaceBuilding {
+                        building,
+                        tile: *anchor,
+                        facing: *facing,
+                        free: false,
+                        record: true,
+                    });
+                }
+            }
+
+            *drag = PlacementDrag::default();
+        }
+    }
+
+    /// Redraws the selectors whenever the dragged area changes
+    pub(super) fn show_preview(
+        mut commands: Commands,
+        drag: Res<PlacementDrag>,
+        state: Res<BuildingState>,
+        map: Option<Res<WorldMap>>,
+        textures: Res<TextureAssets>,
+        preview_query: Query<Entity, With<PlacementPreview>>,
+    ) {
+        if !drag.is_changed() {
+            return;
+        }
+
+        for entity in preview_query.iter() {
+            commands.entity(entity).despawn();
+        }
+
+        let (Some(map), Some(building)) = (map, state.building) else {
+            return;
+        };
+
+        for (anchor, facing, valid) in drag.buildings.iter() {
+            let position = building.centre(&map, *anchor, *facing);
+            let texture = match valid {
+                true => textures.texture_selector.clone(),
+                false => textures.texture_selector_err.clone(),
+            };
+
+            commands.spawn((
+                PlacementPreview,
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(building.size(*facing).as_vec2() * map.block_size),
+                        ..default()
+                    },
+                    texture,
+                    transform: Transform::from_xyz(position.x, position.y, 4.9),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Which way a building faces. It decides the tiles a building covers when
+//! its footprint isn't square and where its entrance is, entrances turn to
+//! face a road when they can.
+
+use std::str::FromStr;
+
+use bevy::prelude::*;
+
+use super::{BuildingType, Road, RoadNetwork};
+use crate::world_gen::WorldMap;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Facing {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Facing {
+    pub const ALL: [Facing; 4] = [Facing::North, Facing::East, Facing::South, Facing::West];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Facing::North => "north",
+            Facing::East => "east",
+            Facing::South => "south",
+            Facing::West => "west",
+        }
+    }
+
+    /// The next facing clockwise
+    pub fn next(&self) -> Self {
+        match self {
+            Facing::North => Facing::East,
+            Facing::East => Facing::South,
+            Facing::South => Facing::West,
+            Facing::West => Facing::North,
+        }
+    }
+
+    /// One tile in the direction it faces
+    pub fn direction(&self) -> IVec2 {
+        match self {
+            Facing::North => IVec2::Y,
+            Facing::East => IVec2::X,
+            Facing::South => IVec2::NEG_Y,
+            Facing::West => IVec2::NEG_X,
+        }
+    }
+
+    /// Turns a sprite drawn facing north to this facing
+    pub fn rotation(&self) -> Quat {
+        let quarter_turns = match self {
+            Facing::North => 0.0,
+            Facing::East => -1.0,
+            Facing::South => 2.0,
+            Facing::West => 1.0,
+        };
+
+        Quat::from_rotation_z(quarter_turns * std::f32::consts::FRAC_PI_2)
+    }
+
+    fn is_sideways(&self) -> bool {
+        matches!(self, Facing::East | Facing::West)
+    }
+
+    /// Gives sprites of newly placed or turned buildings their size, rotation
+    /// or flip
+    #[allow(clippy::type_complexity)]
+    pub(super) fn turn_sprites(
+        map: Option<Res<WorldMap>>,
+        mut building_query: Query<
+            (&BuildingType, &Facing, &mut Sprite, &mut Transform),
+            (Changed<Facing>, Without<Road>),
+        >,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (building, facing, mut sprite, mut transform) in building_query.iter_mut() {
+            building.turn_sprite(*facing, map.block_size, &mut sprite, &mut transform);
+        }
+    }
+}
+
+impl FromStr for Facing {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        Facing::ALL
+            .into_iter()
+            .find(|facing| facing.label().eq_ignore_ascii_case(label))
+            .ok_or_else(|| format!("unknown facing {}", label))
+    }
+}
+
+/// How a building shows the way it faces
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FacingStyle {
+    /// The sprite turns with it, for art drawn from above facing north
+    Rotate,
+    /// The sprite is mirrored when it faces west, for art drawn from the front
+    Flip,
+}
+
+impl BuildingType {
+    /// Width and height in tiles while facing north
+    pub fn footprint(&self) -> IVec2 {
+        match self {
+            BuildingType::Farm => IVec2::new(2, 1),
+            _ => IVec2::ONE,
+        }
+    }
+
+    pub fn facing_style(&self) -> FacingStyle {
+        match self {
+            BuildingType::Farm | BuildingType::Road => FacingStyle::Rotate,
+            _ => FacingStyle::Flip,
+        }
+    }
+
+    /// Width and height in tiles while facing `facing`
+    pub fn size(&self, facing: Facing) -> IVec2 {
+        let footprint = self.footprint();
+
+        match facing.is_sideways() {
+            true => IVec2::new(footprint.y, footprint.x),
+            false => footprint,
+        }
+    }
+
+    /// Every tile the building covers, with `anchor` as its bottom left tile
+    pub fn tiles(&self, anchor: IVec2, facing: Facing) -> Vec<IVec2> {
+        let size = self.size(facing);
+
+        (0..size.x)
+            .flat_map(|x| (0..size.y).map(move |y| anchor + IVec2::new(x, y)))
+            .collect()
+    }
+
+    /// The tile in front of the middle of the side the building faces
+    pub fn entrance(&self, anchor: IVec2, facing: Facing) -> IVec2 {
+        let size = self.size(facing);
+        let middle = (size - IVec2::ONE) / 2;
+
+        match facing {
+            Facing::North => anchor + IVec2::new(middle.x, size.y),
+            Facing::East => anchor + IVec2::new(size.x, middle.y),
+            Facing::South => anchor + IVec2::new(middle.x, -1),
+            Facing::West => anchor + IVec2::new(-1, middle.y),
+        }
+    }
+
+    /// The middle of all the tiles the building covers, in world space
+    pub fn centre(&self, map: &WorldMap, anchor: IVec2, facing: Facing) -> Vec2 {
+        let far_corner = anchor + self.size(facing) - IVec2::ONE;
+
+        (map.tile_to_world(anchor) + map.tile_to_world(far_corner)) / 2.0
+    }
+
+    /// `preferred` when its entrance leads to the town centre, otherwise the
+    /// first facing whose entrance does. Buildings that aren't square only
+    /// turn around, turning sideways would change the tiles they cover.
+    pub fn facing_for_access(
+        &self,
+        network: &RoadNetwork,
+        anchor: IVec2,
+        preferred: Facing,
+    ) -> Facing {
+        let square = self.footprint().x == self.footprint().y;
+
+        std::iter::once(preferred)
+            .chain(Facing::ALL)
+            .filter(|facing| square || facing.is_sideways() == preferred.is_sideways())
+            .find(|facing| network.has_access(self.entrance(anchor, *facing)))
+            .unwrap_or(preferred)
+    }
+
+    /// Sizes, rotates or flips a sprite of the building to face `facing`
+    pub fn turn_sprite(
+        &self,
+        facing: Facing,
+        block_size: f32,
+        sprite: &mut Sprite,
+        transform: &mut Transform,
+    ) {
+        sprite.custom_size = match self.footprint() {
+            IVec2::ONE => None,
+            footprint => Some(footprint.as_vec2() * block_size),
+        };
+
+        match self.facing_style() {
+            FacingStyle::Rotate => {
+                transform.rotation = facing.rotation();
+                sprite.flip_x = false;
+            }
+            FacingStyle::Flip => {
+                transform.rotation = Quat::IDENTITY;
+                sprite.flip_x = facing == Facing::West;
+            }
+        }
+    }
+}
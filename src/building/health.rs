@@ -0,0 +1,279 @@
+//! Damaged buildings: health bars over them, tearing them down once their
+//! health runs out and repairs. Repairs are queued and worked through one at
+//! a time, each paid for up front in proportion to the damage.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use super::{
+    Building, BuildingDestroyed, BuildingPlugin, BuildingType, DemolishBuilding, Facing,
+    RepairBuilding,
+};
+use crate::{
+    economy::{Good, Goods, Stockpile},
+    notifications::{Notification, Severity},
+    tiles::TilePosition,
+    units::Health,
+    world_gen::WorldMap,
+};
+
+/// Health restored each second while a building is being repaired
+const REPAIR_RATE: f32 = 25.0;
+/// What fully repairing the town centre costs
+const TOWN_CENTRE_REPAIR: &Goods = &[(Good::Wood, 100), (Good::Stone, 50)];
+/// Height of a health bar, in pixels
+const BAR_HEIGHT: f32 = 6.0;
+const BAR_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+const BAR_FILL: Color = Color::rgb(0.3, 0.85, 0.3);
+
+/// Shown over a building while it is damaged
+#[derive(Component)]
+pub struct HealthBar;
+
+/// The part of the bar that shrinks with the building's health
+#[derive(Component)]
+pub struct HealthBarFill;
+
+impl BuildingType {
+    /// What it takes to repair the building, the part of its cost that was
+    /// lost to damage
+    pub fn repair_cost(&self, health: &Health) -> Vec<(Good, u32)> {
+        let missing = health.max.saturating_sub(health.current) as f32 / health.max as f32;
+        let cost: &Goods = match self {
+            // Free to place, but not to patch up
+            BuildingType::TownCentre => TOWN_CENTRE_REPAIR,
+            _ => self.cost(),
+        };
+
+        cost.iter()
+            .map(|(good, amount)| (*good, (*amount as f32 * missing).ceil() as u32))
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+}
+
+impl HealthBar {
+    /// Adds bars to buildings that took damage, keeps them up to date and
+    /// takes them away again once the building is back to full health
+    #[allow(clippy::type_complexity)]
+    pub(super) fn update(
+        mut commands: Commands,
+        map: Option<Res<WorldMap>>,
+        building_query: Query<
+            (
+                Entity,
+                &BuildingType,
+                &Facing,
+                &Health,
+                &Transform,
+                Option<&Children>,
+            ),
+            (With<Building>, Changed<Health>),
+        >,
+        bar_query: Query<Entity, With<HealthBar>>,
+        mut fill_query: Query<(&Parent, &mut Transform), (With<HealthBarFill>, Without<Building>)>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (entity, building, facing, health, transform, children) in building_query.iter() {
+            // Being torn down this frame
+            if health.current == 0 {
+                continue;
+            }
+
+            let bar = children.and_then(|children| {
+                children
+                    .iter()
+                    .find(|child| bar_query.contains(**child))
+                    .copied()
+            });
+            let fraction = health.current as f32 / health.max as f32;
+
+            match (bar, health.is_damaged()) {
+                (Some(bar), true) => {
+                    for (parent, mut fill) in fill_query.iter_mut() {
+                        if parent.get() == bar {
+                            fill.scale.x = fraction;
+                        }
+                    }
+                }
+                (Some(bar), false) => {
+                    commands.entity(bar).despawn_recursive();
+                }
+                (None, true) => {
+                    let size = building.size(*facing).as_vec2() * map.block_size;
+                    // Undoes the building's own rotation so the bar stays level
+                    let rotation = transform.rotation.inverse();
+                    let offset = rotation * Vec3::new(0.0, size.y / 2.0 + BAR_HEIGHT, 3.0);
+
+                    commands.entity(entity).with_children(|parent| {
+                        parent
+                            .spawn((
+                                HealthBar,
+                                SpriteBundle {
+                                    sprite: Sprite {
+                                        color: BAR_BACKGROUND,
+                                        custom_size: Some(Vec2::new(size.x, BAR_HEIGHT)),
+                                        ..default()
+                                    },
+                                    transform: Transform::from_translation(offset)
+                                        .with_rotation(rotation),
+                                    ..default()
+                                },
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    HealthBarFill,
+                                    SpriteBundle {
+                                        sprite: Sprite {
+                                            color: BAR_FILL,
+                                            custom_size: Some(Vec2::new(size.x, BAR_HEIGHT)),
+                                            anchor: Anchor::CenterLeft,
+                                            ..default()
+                                        },
+                                        transform: Transform::from_xyz(-size.x / 2.0, 0.0, 0.1)
+                                            .with_scale(Vec3::new(fraction, 1.0, 1.0)),
+                                        ..default()
+                                    },
+                                ));
+                            });
+                    });
+                }
+                (None, false) => {}
+            }
+        }
+    }
+}
+
+impl BuildingPlugin {
+    /// Tears down buildings whose health ran out, which frees their tiles
+    /// the same way demolishing them does
+    #[allow(clippy::type_complexity)]
+    pub(super) fn destroy_wrecks(
+        building_query: Query<
+            (&BuildingType, &TilePosition, &Health),
+            (With<Building>, Changed<Health>),
+        >,
+        mut demolish: EventWriter<DemolishBuilding>,
+        mut destroyed: EventWriter<BuildingDestroyed>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        for (building, tile, health) in building_query.iter() {
+            if health.current > 0 {
+                continue;
+            }
+
+            demolish.send(DemolishBuilding {
+                tile: tile.0,
+                refund: false,
+                record: false,
+            });
+            destroyed.send(BuildingDestroyed {
+                building: *building,
+                tile: tile.0,
+            });
+            notifications.send(
+                Notification::new(
+                    Severity::Danger,
+                    format!("{} was destroyed", building.label()),
+                )
+                .at(tile.0),
+            );
+        }
+    }
+}
+
+/// Buildings waiting to be repaired, the first one is being worked on
+#[derive(Resource, Default)]
+pub struct RepairQueue {
+    jobs: VecDeque<Entity>,
+    /// The first job has been paid for
+    paid: bool,
+    /// Health restored to the first job that hasn't added up to a whole point
+    progress: f32,
+}
+
+impl RepairQueue {
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn contains(&self, building: Entity) -> bool {
+        self.jobs.contains(&building)
+    }
+
+    pub(super) fn queue(
+        mut events: EventReader<RepairBuilding>,
+        mut queue: ResMut<RepairQueue>,
+        building_query: Query<
+            (Entity, &BuildingType, &TilePosition, &Facing, &Health),
+            With<Building>,
+        >,
+    ) {
+        for RepairBuilding { tile } in events.iter() {
+            let damaged = building_query
+                .iter()
+                .find(|(_, building, position, facing, _)| {
+                    building.tiles(position.0, **facing).contains(tile)
+                })
+                .filter(|(.., health)| health.is_damaged())
+                .map(|(entity, ..)| entity);
+
+            if let Some(entity) = damaged.filter(|entity| !queue.contains(*entity)) {
+                queue.jobs.push_back(entity);
+            }
+        }
+    }
+
+    /// Pays for the first job and then restores its health bit by bit. A job
+    /// the stockpile can't pay for yet goes to the back of the queue, so it
+    /// doesn't hold up cheaper ones behind it.
+    pub(super) fn work(
+        time: Res<Time>,
+        mut queue: ResMut<RepairQueue>,
+        mut stockpile: ResMut<Stockpile>,
+        mut building_query: Query<(&BuildingType, &mut Health), With<Building>>,
+    ) {
+        let Some(entity) = queue.jobs.front().copied() else {
+            return;
+        };
+
+        let Ok((building, mut health)) = building_query.get_mut(entity) else {
+            // Destroyed or demolished while it waited
+            queue.next_job();
+            return;
+        };
+
+        if !queue.paid {
+            if !stockpile.spend(&building.repair_cost(&health)) {
+                queue.jobs.rotate_left(1);
+                return;
+            }
+            queue.paid = true;
+        }
+
+        queue.progress += REPAIR_RATE * time.delta_seconds();
+        let restored = queue.progress.floor();
+        if restored >= 1.0 {
+            queue.progress -= restored;
+            health.current = (health.current + restored as u32).min(health.max);
+        }
+
+        if !health.is_damaged() {
+            queue.next_job();
+        }
+    }
+
+    fn next_job(&mut self) {
+        self.jobs.pop_front();
+        self.paid = false;
+        self.progress = 0.0;
+    }
+}
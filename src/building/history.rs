@@ -0,0 +1,228 @@
+//! Undo and redo for placing, demolishing and upgrading buildings. Orders can
+//! be taken back for a few seconds, after that construction starts and they
+//! stick.
+
+use bevy::prelude::*;
+
+use super::{
+    BuildingType, DemolishBuilding, DowngradeBuilding, Facing, PlaceBuilding, UpgradeBuilding,
+};
+use crate::{
+    notifications::{Notification, Severity},
+    tiles::TilePosition,
+};
+
+/// Seconds an order stays in the history, new buildings stay blueprints for
+/// as long
+pub const UNDO_WINDOW: f32 = 10.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BuildingAction {
+    Place {
+        building: BuildingType,
+        tile: IVec2,
+        facing: Facing,
+    },
+    Demolish {
+        building: BuildingType,
+        tile: IVec2,
+        facing: Facing,
+    },
+    Upgrade {
+        tile: IVec2,
+    },
+}
+
+/// Steps back or forward through the history
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HistoryStep {
+    Undo,
+    Redo,
+}
+
+/// Everything done in one go, like a dragged out row of houses
+struct HistoryEntry {
+    actions: Vec<BuildingAction>,
+    /// `Time::elapsed_seconds_f64` when it leaves the history
+    expires: f64,
+}
+
+#[derive(Resource, Default)]
+pub struct BuildingHistory {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// Actions recorded this frame, they become one entry
+    pending: Vec<BuildingAction>,
+}
+
+impl BuildingHistory {
+    pub fn record(&mut self, action: BuildingAction) {
+        self.pending.push(action);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Groups the actions of this frame into one entry, which clears the redo
+    /// steps, and drops entries that are past the undo window
+    pub(super) fn commit(time: Res<Time>, mut history: ResMut<BuildingHistory>) {
+        let now = time.elapsed_seconds_f64();
+
+        if !history.pending.is_empty() {
+            let actions = std::mem::take(&mut history.pending);
+            history.undo.push(HistoryEntry {
+                actions,
+                expires: now + UNDO_WINDOW as f64,
+            });
+            history.redo.clear();
+        }
+
+        if history
+            .undo
+            .iter()
+            .chain(history.redo.iter())
+            .any(|entry| entry.expires <= now)
+        {
+            history.undo.retain(|entry| entry.expires > now);
+            history.redo.retain(|entry| entry.expires > now);
+        }
+    }
+
+    /// Sends the events that reverse or repeat the actions of an entry. None
+    /// of them are recorded again, the entry moves between the undo and redo
+    /// steps instead.
+    pub(super) fn step(
+        mut events: EventReader<HistoryStep>,
+        mut history: ResMut<BuildingHistory>,
+        mut place: EventWriter<PlaceBuilding>,
+        mut demolish: EventWriter<DemolishBuilding>,
+        mut upgrade: EventWriter<UpgradeBuilding>,
+        mut downgrade: EventWriter<DowngradeBuilding>,
+    ) {
+        for step in events.iter() {
+            match step {
+                HistoryStep::Undo => {
+                    let Some(entry) = history.undo.pop() else {
+                        continue;
+                    };
+
+                    for action in entry.actions.iter().rev() {
+                        match *action {
+                            BuildingAction::Place { tile, .. } => {
+                                demolish.send(DemolishBuilding {
+                                    tile,
+                                    refund: true,
+                                    record: false,
+                                });
+                            }
+                            BuildingAction::Demolish {
+                                building,
+                                tile,
+                                facing,
+                            } => {
+                                place.send(PlaceBuilding {
+                                    building,
+                                    tile,
+                                    facing,
+                                    free: true,
+                                    record: false,
+                                });
+                            }
+                            BuildingAction::Upgrade { tile } => {
+                                downgrade.send(DowngradeBuilding { tile });
+                            }
+                        }
+                    }
+
+                    history.redo.push(entry);
+                }
+                HistoryStep::Redo => {
+                    let Some(entry) = history.redo.pop() else {
+                        continue;
+                    };
+
+                    for action in entry.actions.iter() {
+                        match *action {
+                            BuildingAction::Place {
+                                building,
+                                tile,
+                                facing,
+                            } => {
+                                place.send(PlaceBuilding {
+                                    building,
+                                    tile,
+                                    facing,
+                                    free: false,
+                                    record: false,
+                                });
+                            }
+                            BuildingAction::Demolish { tile, .. } => {
+                                demolish.send(DemolishBuilding {
+                                    tile,
+                                    refund: false,
+                                    record: false,
+                                });
+                            }
+                            BuildingAction::Upgrade { tile } => {
+                                upgrade.send(UpgradeBuilding {
+                                    tile,
+                                    record: false,
+                                });
+                            }
+                        }
+                    }
+
+                    history.undo.push(entry);
+                }
+            }
+        }
+    }
+}
+
+/// A building that was just ordered. It stays see-through until the undo
+/// window closes and construction starts.
+#[derive(Component)]
+pub struct Blueprint(Timer);
+
+impl Default for Blueprint {
+    fn default() -> Self {
+        Self(Timer::from_seconds(UNDO_WINDOW, TimerMode::Once))
+    }
+}
+
+impl Blueprint {
+    pub(super) fn start_construction(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut blueprint_query: Query<(
+            Entity,
+            &mut Blueprint,
+            &mut Sprite,
+            &BuildingType,
+            &TilePosition,
+        )>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        for (entity, mut blueprint, mut sprite, building, tile) in blueprint_query.iter_mut() {
+            if blueprint.0.tick(time.delta()).finished() {
+                commands.entity(entity).remove::<Blueprint>();
+                sprite.color.set_a(1.0);
+
+                // Roads go down a tile at a time, too many to tell about
+                if *building != BuildingType::Road {
+                    notifications.send(
+                        Notification::new(Severity::Info, format!("{} finished", building.label()))
+                            .at(tile.0),
+                    );
+                }
+            } else {
+                sprite.color.set_a(0.5);
+            }
+        }
+    }
+}
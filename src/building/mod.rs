@@ -0,0 +1,967 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+
+use crate::{
+    economy::{Production, Stockpile},
+    loading::{BuildingAssets, FontAssets, TextureAssets},
+    mouse_position::MousePosition,
+    notifications::{Notification, Severity},
+    research::Research,
+    tiles::{Tile, TilePosition},
+    ui::{GuiPluginLabels, PanelState},
+    units::Health,
+    world_gen::WorldMap,
+    GameState,
+};
+
+mod drag;
+mod facing;
+mod health;
+mod history;
+mod placement;
+mod roads;
+mod territory;
+
+pub use facing::{Facing, FacingStyle};
+pub use health::{HealthBar, RepairQueue};
+pub use history::{Blueprint, BuildingAction, BuildingHistory, HistoryStep, UNDO_WINDOW};
+pub use placement::{PlacementError, PlacementRule};
+pub use roads::{Road, RoadAccess, RoadNetwork, RoadShape, ROAD_SPEED_BONUS};
+pub use territory::Territory;
+
+use drag::PlacementDrag;
+use territory::TerritoryBorder;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BuildingType {
+    TownCentre,
+    Barracks,
+    Farm,
+    HouseOne,
+    HouseTwo,
+    House,
+    Shade,
+    Stall,
+    Road,
+    LumberMill,
+    Stonecutter,
+}
+
+impl BuildingType {
+    pub const ALL: [BuildingType; 11] = [
+        BuildingType::TownCentre,
+        BuildingType::Barracks,
+        BuildingType::Farm,
+        BuildingType::HouseOne,
+        BuildingType::HouseTwo,
+        BuildingType::House,
+        BuildingType::Shade,
+        BuildingType::Stall,
+        BuildingType::Road,
+        BuildingType::LumberMill,
+        BuildingType::Stonecutter,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BuildingType::TownCentre => "Town Centre",
+            BuildingType::Barracks => "Barracks",
+            BuildingType::Farm => "Farm",
+            BuildingType::HouseOne | BuildingType::HouseTwo | BuildingType::House => "House",
+            BuildingType::Shade => "Shade",
+            BuildingType::Stall => "Stall",
+            BuildingType::Road => "Road",
+            BuildingType::LumberMill => "Lumber Mill",
+            BuildingType::Stonecutter => "Stonecutter",
+        }
+    }
+
+    /// Stable name used in save files
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildingType::TownCentre => "TownCentre",
+            BuildingType::Barracks => "Barracks",
+            BuildingType::Farm => "Farm",
+            BuildingType::HouseOne => "HouseOne",
+            BuildingType::HouseTwo => "HouseTwo",
+            BuildingType::House => "House",
+            BuildingType::Shade => "Shade",
+            BuildingType::Stall => "Stall",
+            BuildingType::Road => "Road",
+            BuildingType::LumberMill => "LumberMill",
+            BuildingType::Stonecutter => "Stonecutter",
+        }
+    }
+
+    fn generic_build(
+        &self,
+        commands: &mut Commands,
+        textures: &BuildingAssets,
+        position: Vec2,
+    ) -> Entity {
+        commands
+            .spawn((
+                Building,
+                *self,
+                SpriteBundle {
+                    texture: self.get_texture(textures),
+                    transform: Transform::from_xyz(position.x, position.y, 1.0),
+                    ..default()
+                },
+            ))
+            .id()
+    }
+
+    fn get_entity(
+        &self,
+        commands: &mut Commands,
+        textures: &BuildingAssets,
+        position: Vec2,
+    ) -> Option<Entity> {
+        match self {
+            BuildingType::TownCentre => Some(TownCentre::build(commands, textures, position)),
+            BuildingType::Road => Some(Road::build(commands, textures, position)),
+            _ => Some(self.generic_build(commands, textures, position)),
+        }
+    }
+
+    pub fn get_texture(&self, textures: &BuildingAssets) -> Handle<Image> {
+        match self {
+            BuildingType::TownCentre => textures.town_centre.clone(),
+            BuildingType::Barracks => textures.barracks.clone(),
+            BuildingType::Farm => textures.farm.clone(),
+            BuildingType::HouseOne => textures.house_1.clone(),
+            BuildingType::HouseTwo => textures.house_2.clone(),
+            BuildingType::House => textures.house.clone(),
+            BuildingType::Shade => textures.shade.clone(),
+            BuildingType::Stall => textures.stall.clone(),
+            BuildingType::Road => textures.road_straight.clone(),
+            BuildingType::LumberMill => textures.lumber_mill.clone(),
+            BuildingType::Stonecutter => textures.stonecutter.clone(),
+        }
+    }
+
+    /// How much damage the building takes before it is destroyed
+    pub fn max_health(&self) -> u32 {
+        match self {
+            BuildingType::TownCentre => 500,
+            BuildingType::Barracks => 300,
+            BuildingType::Road => 50,
+            _ => 150,
+        }
+    }
+}
+
+impl FromStr for BuildingType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        BuildingType::ALL
+            .into_iter()
+            .find(|building| building.name() == name)
+            .ok_or_else(|| format!("unknown building {}", name))
+    }
+}
+
+#[derive(Component)]
+pub struct Buildable;
+
+#[derive(Component)]
+pub struct Building;
+
+#[derive(Component)]
+pub struct TownCentre;
+
+impl TownCentre {
+    pub fn build(commands: &mut Commands, textures: &BuildingAssets, position: Vec2) -> Entity {
+        commands
+            .spawn((
+                Building,
+                TownCentre,
+                BuildingType::TownCentre,
+                SpriteBundle {
+                    texture: BuildingType::TownCentre.get_texture(textures),
+                    transform: Transform::from_xyz(position.x, position.y, 1.0),
+                    ..default()
+                },
+            ))
+            .id()
+    }
+}
+
+#[derive(Resource)]
+pub struct BuildingState {
+    pub mode_active: bool,
+    pub building: Option<BuildingType>,
+    /// Which way new buildings face, turned with R
+    pub facing: Facing,
+}
+
+impl Default for BuildingState {
+    fn default() -> Self {
+        Self {
+            mode_active: false,
+            building: None,
+            facing: Facing::North,
+        }
+    }
+}
+
+// Start - Building Events
+pub struct BuildingModeChange {
+    pub state: bool,
+    pub building: Option<BuildingType>,
+}
+pub struct TownCentreBuilt;
+/// Asks for `building` to be placed with its bottom left corner on `tile`,
+/// ignored when it hasn't been researched, its placement rules fail or the
+/// stockpile can't pay for it
+pub struct PlaceBuilding {
+    pub building: BuildingType,
+    pub tile: IVec2,
+    /// Turned to face a road instead when this side has none
+    pub facing: Facing,
+    /// Skips the cost and the research, for buildings restored from a save
+    pub free: bool,
+    /// Adds the placement to the undo history
+    pub record: bool,
+}
+/// Asks for the building on `tile` to be torn down
+pub struct DemolishBuilding {
+    pub tile: IVec2,
+    /// Gives back what the building cost, when undoing its placement
+    pub refund: bool,
+    /// Adds the demolition to the undo history
+    pub record: bool,
+}
+/// Asks for the building on `tile` to be upgraded, ignored when it has no
+/// upgrade, the upgrade hasn't been researched or the stockpile can't pay for it
+pub struct UpgradeBuilding {
+    pub tile: IVec2,
+    /// Adds the upgrade to the undo history
+    pub record: bool,
+}
+/// Turns the building on `tile` back into what it was before its upgrade and
+/// refunds the upgrade, for undoing it
+pub struct DowngradeBuilding {
+    pub tile: IVec2,
+}
+/// Sent when a building's health runs out and it is torn down
+pub struct BuildingDestroyed {
+    pub building: BuildingType,
+    pub tile: IVec2,
+}
+/// Queues a repair of the damaged building on `tile`
+pub struct RepairBuilding {
+    pub tile: IVec2,
+}
+// End - Building Events
+
+/// Building state and placement. Works without a window, the mouse driven
+/// parts live in `BuildingIndicatorPlugin`.
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BuildingModeChange>()
+            .add_event::<TownCentreBuilt>()
+            .add_event::<PlaceBuilding>()
+            .add_event::<DemolishBuilding>()
+            .add_event::<UpgradeBuilding>()
+            .add_event::<DowngradeBuilding>()
+            .add_event::<BuildingDestroyed>()
+            .add_event::<RepairBuilding>()
+            .add_event::<HistoryStep>()
+            .insert_resource(BuildingState::default())
+            .init_resource::<Territory>()
+            .init_resource::<RoadNetwork>()
+            .init_resource::<BuildingHistory>()
+            .init_resource::<RepairQueue>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(BuildingPlugin::listen_build_mode_event)
+                    .with_system(BuildingHistory::step)
+                    .with_system(BuildingPlugin::on_place_building.after(BuildingHistory::step))
+                    .with_system(BuildingPlugin::on_demolish_building.after(BuildingHistory::step))
+                    .with_system(BuildingPlugin::on_upgrade_building.after(BuildingHistory::step))
+                    .with_system(BuildingPlugin::on_downgrade_building.after(BuildingHistory::step))
+                    .with_system(
+                        BuildingHistory::commit
+                            .after(BuildingPlugin::on_place_building)
+                            .after(BuildingPlugin::on_demolish_building)
+                            .after(BuildingPlugin::on_upgrade_building),
+                    )
+                    .with_system(Blueprint::start_construction)
+                    .with_system(Facing::turn_sprites)
+                    .with_system(RoadNetwork::track_buildings)
+                    .with_system(Road::update_sprites.after(RoadNetwork::track_buildings))
+                    .with_system(RoadAccess::update.after(RoadNetwork::track_buildings))
+                    .with_system(BuildingPlugin::destroy_wrecks)
+                    .with_system(RepairQueue::queue)
+                    .with_system(RepairQueue::work.after(RepairQueue::queue))
+                    .with_system(HealthBar::update.after(RepairQueue::work)),
+            );
+    }
+}
+
+impl BuildingPlugin {
+    /// Spawns `building` on `tile` and marks the tiles it covers as occupied.
+    /// Removing `Buildable` from the tile entities is left to the caller.
+    pub fn place_building(
+        commands: &mut Commands,
+        textures: &BuildingAssets,
+        map: &mut WorldMap,
+        building: BuildingType,
+        tile: IVec2,
+        facing: Facing,
+    ) -> Option<Entity> {
+        let position = building.centre(map, tile, facing);
+        let entity = building.get_entity(commands, textures, position)?;
+
+        commands.entity(entity).insert((
+            TilePosition(tile),
+            facing,
+            Health::new(building.max_health()),
+        ));
+
+        for tile in building.tiles(tile, facing) {
+            if let Some(map_tile) = map.get_mut(tile) {
+                map_tile.occupied = true;
+            }
+        }
+
+        Some(entity)
+    }
+
+    fn listen_build_mode_event(
+        mut event: EventReader<BuildingModeChange>,
+        mut state: ResMut<BuildingState>,
+    ) {
+        if !event.is_empty() {
+            for change in event.iter() {
+                state.mode_active = change.state;
+                state.building = change.building;
+            }
+        }
+
+        event.clear();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_place_building(
+        mut commands: Commands,
+        mut events: EventReader<PlaceBuilding>,
+        mut state: ResMut<BuildingState>,
+        mut territory: ResMut<Territory>,
+        mut stockpile: ResMut<Stockpile>,
+        mut history: ResMut<BuildingHistory>,
+        network: Res<RoadNetwork>,
+        research: Res<Research>,
+        map: Option<ResMut<WorldMap>>,
+        textures: Res<BuildingAssets>,
+        tile_query: Query<(Entity, &TilePosition), With<Buildable>>,
+        mut town_centre_built: EventWriter<TownCentreBuilt>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        let Some(mut map) = map else {
+            return;
+        };
+
+        for PlaceBuilding {
+            building,
+            tile,
+            facing,
+            free,
+            record,
+        } in events.iter()
+        {
+            if !free && !research.is_unlocked(*building) {
+                continue;
+            }
+
+            let facing = building.facing_for_access(&network, *tile, *facing);
+
+            if building
+                .check_placement(&map, &territory, *tile, facing)
+                .is_err()
+            {
+                continue;
+            }
+
+            if !free && !stockpile.spend(building.cost()) {
+                continue;
+            }
+
+            let Some(entity) = BuildingPlugin::place_building(
+                &mut commands,
+                &textures,
+                &mut map,
+                *building,
+                *tile,
+                facing,
+            ) else {
+                continue;
+            };
+
+            let tiles = building.tiles(*tile, facing);
+            for (tile_entity, position) in tile_query.iter() {
+                if tiles.contains(&position.0) {
+                    commands.entity(tile_entity).remove::<Buildable>();
+                }
+            }
+
+            if let Some(radius) = building.territory_radius() {
+                territory.claim(*tile, radius);
+            }
+
+            if *building == BuildingType::TownCentre {
+                town_centre_built.send(TownCentreBuilt);
+                notifications.send(
+                    Notification::new(
+                        Severity::Good,
+                        "Town centre built, the first workers have moved in",
+                    )
+                    .at(*tile),
+                );
+                state.mode_active = false;
+                state.building = None;
+                continue;
+            }
+
+            if !free {
+                commands.entity(entity).insert(Blueprint::default());
+            }
+
+            if *record {
+                history.record(BuildingAction::Place {
+                    building: *building,
+                    tile: *tile,
+                    facing,
+                });
+            }
+        }
+    }
+
+    /// Removes the building, frees its tile and gives back the territory and
+    /// roads it brought, and the goods waiting in its buffers
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn on_demolish_building(
+        mut commands: Commands,
+        mut events: EventReader<DemolishBuilding>,
+        mut territory: ResMut<Territory>,
+        mut network: ResMut<RoadNetwork>,
+        mut stockpile: ResMut<Stockpile>,
+        mut history: ResMut<BuildingHistory>,
+        map: Option<ResMut<WorldMap>>,
+        building_query: Query<
+            (
+                Entity,
+                &BuildingType,
+                &TilePosition,
+                &Facing,
+                Option<&Production>,
+            ),
+            With<Building>,
+        >,
+        tile_query: Query<(Entity, &TilePosition), With<Tile>>,
+    ) {
+        let Some(mut map) = map else {
+            return;
+        };
+
+        for DemolishBuilding {
+            tile,
+            refund,
+            record,
+        } in events.iter()
+        {
+            let Some((entity, building, _, facing, production)) = building_query
+                .iter()
+                .find(|(_, _, position, ..)| position.0 == *tile)
+            else {
+                continue;
+            };
+            // Already torn down earlier this frame
+            if !map.get(*tile).is_some_and(|map_tile| map_tile.occupied) {
+                continue;
+            }
+
+            commands.entity(entity).despawn_recursive();
+
+            let tiles = building.tiles(*tile, *facing);
+            for tile in tiles.iter() {
+                if let Some(map_tile) = map.get_mut(*tile) {
+                    map_tile.occupied = false;
+                }
+            }
+            for (tile_entity, position) in tile_query.iter() {
+                let buildable = map.get(position.0).is_some_and(|tile| tile.is_buildable());
+
+                if tiles.contains(&position.0) && buildable {
+                    commands.entity(tile_entity).insert(Buildable);
+                }
+            }
+
+            match building {
+                BuildingType::Road => network.remove_road(*tile),
+                BuildingType::TownCentre => network.set_town_centre(None),
+                _ => {}
+            }
+
+            if let Some(radius) = building.territory_radius() {
+                territory.unclaim(*tile, radius);
+            }
+
+            if *refund {
+                stockpile.add(building.cost());
+            }
+            if let Some(production) = production {
+                stockpile.add(&production.buffered_goods());
+            }
+
+            if *record {
+                history.record(BuildingAction::Demolish {
+                    building: *building,
+                    tile: *tile,
+                    facing: *facing,
+                });
+            }
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn on_upgrade_building(
+        mut commands: Commands,
+        mut events: EventReader<UpgradeBuilding>,
+        mut stockpile: ResMut<Stockpile>,
+        mut history: ResMut<BuildingHistory>,
+        research: Res<Research>,
+        textures: Res<BuildingAssets>,
+        mut building_query: Query<
+            (Entity, &mut BuildingType, &mut Handle<Image>, &TilePosition),
+            With<Building>,
+        >,
+    ) {
+        for UpgradeBuilding { tile, record } in events.iter() {
+            let Some((entity, mut building, mut texture, _)) = building_query
+                .iter_mut()
+                .find(|(_, _, _, position)| position.0 == *tile)
+            else {
+                continue;
+            };
+            let Some((upgrade, cost)) = building.upgrade() else {
+                continue;
+            };
+
+            if !research.is_unlocked(upgrade) || !stockpile.spend(cost) {
+                continue;
+            }
+
+            *building = upgrade;
+            *texture = upgrade.get_texture(&textures);
+            commands.entity(entity).insert(Blueprint::default());
+
+            if *record {
+                history.record(BuildingAction::Upgrade { tile: *tile });
+            }
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn on_downgrade_building(
+        mut commands: Commands,
+        mut events: EventReader<DowngradeBuilding>,
+        mut stockpile: ResMut<Stockpile>,
+        textures: Res<BuildingAssets>,
+        mut building_query: Query<
+            (
+                Entity,
+                &mut BuildingType,
+                &mut Handle<Image>,
+                &mut Sprite,
+                &TilePosition,
+            ),
+            With<Building>,
+        >,
+    ) {
+        for DowngradeBuilding { tile } in events.iter() {
+            let Some((entity, mut building, mut texture, mut sprite, _)) = building_query
+                .iter_mut()
+                .find(|(_, _, _, _, position)| position.0 == *tile)
+            else {
+                continue;
+            };
+            let Some(downgrade) = building.downgrade() else {
+                continue;
+            };
+
+            if let Some((_, cost)) = downgrade.upgrade() {
+                stockpile.add(cost);
+            }
+
+            *building = downgrade;
+            *texture = downgrade.get_texture(&textures);
+            // Undoing the upgrade also takes back its construction
+            commands.entity(entity).remove::<Blueprint>();
+            sprite.color.set_a(1.0);
+        }
+    }
+}
+
+/// The interactive side of building: the tile indicator, clicking to place,
+/// dragging out roads and areas of buildings, Escape to leave build mode,
+/// demolish, upgrade and undo keys and the territory border
+pub struct BuildingIndicatorPlugin;
+
+impl Plugin for BuildingIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlacementDrag>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing).with_system(BuildingIndicator::spawn),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(BuildingIndicator::track_position)
+                    .with_system(BuildingIndicator::show_placement_reason)
+                    .with_system(BuildingIndicator::track_visibility)
+                    .with_system(
+                        BuildingIndicator::show_ghost.after(BuildingIndicator::track_position),
+                    )
+                    .with_system(TerritoryBorder::redraw)
+                    .with_system(BuildingIndicatorPlugin::cancel_build_mode)
+                    .with_system(BuildingIndicatorPlugin::rotate_building)
+                    .with_system(BuildingIndicatorPlugin::undo_redo)
+                    .with_system(BuildingIndicatorPlugin::demolish_or_upgrade)
+                    .with_system(
+                        BuildingIndicatorPlugin::on_building_placed
+                            .after(GuiPluginLabels::EnterBuildingMode),
+                    )
+                    .with_system(
+                        BuildingIndicatorPlugin::draw_road
+                            .after(GuiPluginLabels::EnterBuildingMode),
+                    )
+                    .with_system(PlacementDrag::track.after(GuiPluginLabels::EnterBuildingMode))
+                    .with_system(PlacementDrag::show_preview.after(PlacementDrag::track)),
+            );
+    }
+}
+
+impl BuildingIndicatorPlugin {
+    fn cancel_build_mode(keys: Res<Input<KeyCode>>, mut state: ResMut<BuildingState>) {
+        if keys.just_pressed(KeyCode::Escape) {
+            state.mode_active = false;
+            state.building = None;
+        }
+    }
+
+    /// R turns the building about to be placed clockwise
+    fn rotate_building(keys: Res<Input<KeyCode>>, mut state: ResMut<BuildingState>) {
+        if state.mode_active && keys.just_pressed(KeyCode::R) {
+            state.facing = state.facing.next();
+        }
+    }
+
+    /// Ctrl+Z undoes the last order, Ctrl+Y (or Ctrl+Shift+Z) redoes it
+    fn undo_redo(keys: Res<Input<KeyCode>>, mut event: EventWriter<HistoryStep>) {
+        if !keys.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+            return;
+        }
+        let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+        if keys.just_pressed(KeyCode::Y) || (shift && keys.just_pressed(KeyCode::Z)) {
+            event.send(HistoryStep::Redo);
+        } else if keys.just_pressed(KeyCode::Z) {
+            event.send(HistoryStep::Undo);
+        }
+    }
+
+    /// Outside of build mode, Delete tears down the building under the mouse,
+    /// U upgrades it and F queues a repair. The town centre can't be torn
+    /// down.
+    #[allow(clippy::too_many_arguments)]
+    fn demolish_or_upgrade(
+        keys: Res<Input<KeyCode>>,
+        state: Res<BuildingState>,
+        mouse: Res<MousePosition>,
+        map: Option<Res<WorldMap>>,
+        building_query: Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+        mut demolish: EventWriter<DemolishBuilding>,
+        mut upgrade: EventWriter<UpgradeBuilding>,
+        mut repair: EventWriter<RepairBuilding>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        if state.mode_active {
+            return;
+        }
+
+        let hovered = map.world_to_tile(mouse.world);
+        let Some((building, tile)) = building_query
+            .iter()
+            .find(|(building, position, facing)| {
+                building.tiles(position.0, **facing).contains(&hovered)
+            })
+            .map(|(building, position, _)| (building, position.0))
+        else {
+            return;
+        };
+
+        if keys.just_pressed(KeyCode::Delete) && *building != BuildingType::TownCentre {
+            demolish.send(DemolishBuilding {
+                tile,
+                refund: false,
+                record: true,
+            });
+        } else if keys.just_pressed(KeyCode::U) {
+            upgrade.send(UpgradeBuilding { tile, record: true });
+        } else if keys.just_pressed(KeyCode::F) {
+            repair.send(RepairBuilding { tile });
+        }
+    }
+
+    fn on_building_placed(
+        buttons: Res<Input<MouseButton>>,
+        state: Res<BuildingState>,
+        indicator_query: Query<&BuildingIndicator>,
+        panel_state: Res<PanelState>,
+        mut event: EventWriter<PlaceBuilding>,
+    ) {
+        let dragged = state
+            .building
+            .is_some_and(|building| building == BuildingType::Road || building.is_repeatable());
+        if dragged {
+            return;
+        }
+
+        if buttons.just_pressed(MouseButton::Left) && state.mode_active && !panel_state.active {
+            let indicator = indicator_query.single();
+
+            if !indicator.valid_tile {
+                return;
+            }
+
+            if let (Some(tile), Some(building)) = (indicator.tile, state.building) {
+                event.send(PlaceBuilding {
+                    building,
+                    tile,
+                    facing: indicator.facing,
+                    free: false,
+                    record: true,
+                });
+            }
+        }
+    }
+
+    /// Lays road on every tile the mouse passes over while it is held down.
+    /// Tiles skipped by a quick mouse are filled in, so the road stays joined
+    /// up, and tiles that can't take a road are left out.
+    fn draw_road(
+        buttons: Res<Input<MouseButton>>,
+        state: Res<BuildingState>,
+        indicator_query: Query<&BuildingIndicator>,
+        panel_state: Res<PanelState>,
+        mut last_tile: Local<Option<IVec2>>,
+        mut event: EventWriter<PlaceBuilding>,
+    ) {
+        let drawing = state.mode_active && state.building == Some(BuildingType::Road);
+
+        if !drawing || panel_state.active || !buttons.pressed(MouseButton::Left) {
+            *last_tile = None;
+            return;
+        }
+
+        let Some(tile) = indicator_query.single().tile else {
+            return;
+        };
+        if *last_tile == Some(tile) {
+            return;
+        }
+
+        let from = last_tile.unwrap_or(tile);
+        let corner = IVec2::new(tile.x, from.y);
+
+        for tile in BuildingIndicatorPlugin::line(from, corner)
+            .chain(BuildingIndicatorPlugin::line(corner, tile))
+        {
+            event.send(PlaceBuilding {
+                building: BuildingType::Road,
+                tile,
+                facing: Facing::North,
+                free: false,
+                record: true,
+            });
+        }
+
+        *last_tile = Some(tile);
+    }
+
+    /// Every tile from `from` to `to` along a row or column
+    fn line(from: IVec2, to: IVec2) -> impl Iterator<Item = IVec2> {
+        let step = (to - from).signum();
+        let length = (to - from).abs().max_element();
+
+        (0..=length).map(move |i| from + step * i)
+    }
+}
+
+#[derive(Component)]
+pub struct BuildingIndicator {
+    valid_tile: bool,
+    tile: Option<IVec2>,
+    /// The way the building would face, turned towards a road when it can
+    facing: Facing,
+    /// Why the building can't go on the tile under the mouse
+    error: Option<PlacementError>,
+}
+
+/// Text above the indicator explaining an invalid spot
+#[derive(Component)]
+struct PlacementReason;
+
+/// See-through preview of the building, facing the way it will be placed
+#[derive(Component)]
+struct IndicatorGhost;
+
+impl BuildingIndicator {
+    fn spawn(mut commands: Commands, textures: Res<TextureAssets>, fonts: Res<FontAssets>) {
+        commands
+            .spawn((
+                BuildingIndicator {
+                    valid_tile: false,
+                    tile: None,
+                    facing: Facing::North,
+                    error: None,
+                },
+                SpriteBundle {
+                    texture: textures.texture_selector.clone(),
+                    transform: Transform::from_xyz(0.0, 0.0, 5.0),
+                    visibility: Visibility { is_visible: false },
+                    ..default()
+                },
+                Name::new("BuildingIndicator"),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    IndicatorGhost,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, -0.1),
+                        ..default()
+                    },
+                ));
+                parent.spawn((
+                    PlacementReason,
+                    Text2dBundle {
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font: fonts.fira_sans.clone(),
+                                font_size: 16.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_alignment(TextAlignment::CENTER),
+                        transform: Transform::from_xyz(0.0, 44.0, 1.0),
+                        ..default()
+                    },
+                ));
+            });
+    }
+
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn track_position(
+        mut indicator_query: Query<(
+            &mut Transform,
+            &mut Sprite,
+            &mut Handle<Image>,
+            &mut BuildingIndicator,
+        )>,
+        map: Option<Res<WorldMap>>,
+        territory: Res<Territory>,
+        network: Res<RoadNetwork>,
+        state: Res<BuildingState>,
+        mouse: Res<MousePosition>,
+        textures: Res<TextureAssets>,
+    ) {
+        let (Some(map), Some(building)) = (map, state.building) else {
+            return;
+        };
+        let (mut transform, mut sprite, mut texture, mut indicator) = indicator_query.single_mut();
+
+        let tile = map.world_to_tile(mouse.world);
+        let facing = building.facing_for_access(&network, tile, state.facing);
+        let position = building.centre(&map, tile, facing);
+        transform.translation = Vec3::new(position.x, position.y, 5.0);
+
+        let error = building
+            .check_placement(&map, &territory, tile, facing)
+            .err();
+        if indicator.tile == Some(tile) && indicator.facing == facing && indicator.error == error {
+            return;
+        }
+
+        *texture = match error {
+            Some(_) => textures.texture_selector_err.clone(),
+            None => textures.texture_selector.clone(),
+        };
+        sprite.custom_size = Some(building.size(facing).as_vec2() * map.block_size);
+        indicator.valid_tile = error.is_none();
+        indicator.tile = Some(tile);
+        indicator.facing = facing;
+        indicator.error = error;
+    }
+
+    fn show_ghost(
+        indicator_query: Query<&BuildingIndicator>,
+        mut ghost_query: Query<
+            (&mut Handle<Image>, &mut Sprite, &mut Transform),
+            With<IndicatorGhost>,
+        >,
+        map: Option<Res<WorldMap>>,
+        state: Res<BuildingState>,
+        textures: Res<BuildingAssets>,
+    ) {
+        let (Some(map), Some(building)) = (map, state.building) else {
+            return;
+        };
+        let indicator = indicator_query.single();
+
+        for (mut texture, mut sprite, mut transform) in ghost_query.iter_mut() {
+            *texture = building.get_texture(&textures);
+            building.turn_sprite(
+                indicator.facing,
+                map.block_size,
+                &mut sprite,
+                &mut transform,
+            );
+        }
+    }
+
+    fn show_placement_reason(
+        indicator_query: Query<&BuildingIndicator, Changed<BuildingIndicator>>,
+        mut text_query: Query<&mut Text, With<PlacementReason>>,
+    ) {
+        let Ok(indicator) = indicator_query.get_single() else {
+            return;
+        };
+
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = indicator
+                .error
+                .map_or(String::new(), |error| error.reason());
+        }
+    }
+
+    fn track_visibility(
+        mut query: Query<&mut Visibility, With<BuildingIndicator>>,
+        state: Res<BuildingState>,
+    ) {
+        let mut visiblity = query.single_mut();
+        visiblity.is_visible = state.mode_active;
+    }
+}
@@ -0,0 +1,140 @@
+//! Where each building type is allowed to go.
+
+use bevy::prelude::*;
+
+use super::{territory::Territory, BuildingType, Facing};
+use crate::{tiles::*, world_gen::WorldMap};
+
+/// Terrain any building can stand on, unless its rules say otherwise
+const LAND: &[TileType] = &[TileType::Grass, TileType::Dirt, TileType::Stone];
+
+const CARDINALS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementRule {
+    /// The tile has to be one of these terrain types
+    OnTerrain(&'static [TileType]),
+    /// One of the four tiles next to it has to be this terrain type, e.g. a
+    /// dock next to water
+    NextTo(TileType),
+}
+
+/// Why a building can't go on a tile
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+    OutOfBounds,
+    OutsideTerritory,
+    Occupied,
+    Resource(ResourceKind),
+    WrongTerrain(BuildingType, &'static [TileType]),
+    NotNextTo(BuildingType, TileType),
+}
+
+impl PlacementError {
+    /// Shown next to the building indicator
+    pub fn reason(&self) -> String {
+        match self {
+            PlacementError::OutOfBounds => "Outside of the map".to_string(),
+            PlacementError::OutsideTerritory => "Outside of your territory".to_string(),
+            PlacementError::Occupied => "Something is already built here".to_string(),
+            PlacementError::Resource(kind) => format!("Clear the {} first", kind.label()),
+            PlacementError::WrongTerrain(building, allowed) => {
+                let allowed = allowed
+                    .iter()
+                    .map(|tile_type| tile_type.label())
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+
+                format!("{} has to be built on {}", building.label(), allowed)
+            }
+            PlacementError::NotNextTo(building, tile_type) => {
+                format!(
+                    "{} has to be next to {}",
+                    building.label(),
+                    tile_type.label()
+                )
+            }
+        }
+    }
+}
+
+impl BuildingType {
+    pub fn placement_rules(&self) -> &'static [PlacementRule] {
+        match self {
+            BuildingType::Farm => &[PlacementRule::OnTerrain(&[TileType::Grass, TileType::Dirt])],
+            BuildingType::Road => &[PlacementRule::OnTerrain(&[
+                TileType::Grass,
+                TileType::Dirt,
+                TileType::Stone,
+                TileType::Sand,
+            ])],
+            _ => &[PlacementRule::OnTerrain(LAND)],
+        }
+    }
+
+    /// Checks every tile the building covers while facing `facing` is free,
+    /// inside the territory (the town centre is the exception, it claims the
+    /// first territory) and that every placement rule of the building holds
+    pub fn check_placement(
+        &self,
+        map: &WorldMap,
+        territory: &Territory,
+        anchor: IVec2,
+        facing: Facing,
+    ) -> Result<(), PlacementError> {
+        let tiles = self.tiles(anchor, facing);
+
+        for tile in tiles.iter() {
+            self.check_tile(map, territory, *tile)?;
+        }
+
+        for rule in self.placement_rules() {
+            if let PlacementRule::NextTo(tile_type) = rule {
+                let next_to = tiles.iter().any(|tile| {
+                    CARDINALS.iter().any(|offset| {
+                        map.get(*tile + *offset)
+                            .is_some_and(|neighbour| neighbour.tile_type == *tile_type)
+                    })
+                });
+
+                if !next_to {
+                    return Err(PlacementError::NotNextTo(*self, *tile_type));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The checks that hold for each tile on its own
+    fn check_tile(
+        &self,
+        map: &WorldMap,
+        territory: &Territory,
+        tile: IVec2,
+    ) -> Result<(), PlacementError> {
+        let map_tile = map.get(tile).ok_or(PlacementError::OutOfBounds)?;
+
+        if *self != BuildingType::TownCentre && !territory.contains(tile) {
+            return Err(PlacementError::OutsideTerritory);
+        }
+
+        if map_tile.occupied {
+            return Err(PlacementError::Occupied);
+        }
+
+        if let Some(kind) = map_tile.resource {
+            return Err(PlacementError::Resource(kind));
+        }
+
+        for rule in self.placement_rules() {
+            if let PlacementRule::OnTerrain(allowed) = rule {
+                if !allowed.contains(&map_tile.tile_type) {
+                    return Err(PlacementError::WrongTerrain(*self, allowed));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! Roads and the network they form. A building only works while its entrance
+//! opens onto the town centre or onto a road that leads back to it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use super::{Building, BuildingType, Facing, TownCentre};
+use crate::{loading::BuildingAssets, tiles::TilePosition};
+
+/// How much faster units walk while on a road
+pub const ROAD_SPEED_BONUS: f32 = 1.5;
+
+/// North, east, south and west, in the order the sprites are drawn in
+const SIDES: [IVec2; 4] = [IVec2::Y, IVec2::X, IVec2::NEG_Y, IVec2::NEG_X];
+
+/// Tint for buildings that have no road to the town centre
+const DISCONNECTED_TINT: Color = Color::rgb(0.6, 0.6, 0.6);
+
+#[derive(Component)]
+pub struct Road;
+
+impl Road {
+    pub fn build(commands: &mut Commands, textures: &BuildingAssets, position: Vec2) -> Entity {
+        commands
+            .spawn((
+                Building,
+                Road,
+                BuildingType::Road,
+                SpriteBundle {
+                    texture: RoadShape::Single.texture(textures),
+                    transform: Transform::from_xyz(position.x, position.y, 0.9),
+                    ..default()
+                },
+            ))
+            .id()
+    }
+
+    /// Picks the straight, corner or junction sprite for every road once the
+    /// network changes
+    pub(super) fn update_sprites(
+        network: Res<RoadNetwork>,
+        textures: Res<BuildingAssets>,
+        mut road_query: Query<(&TilePosition, &mut Handle<Image>, &mut Transform), With<Road>>,
+    ) {
+        if !network.is_changed() {
+            return;
+        }
+
+        for (tile, mut texture, mut transform) in road_query.iter_mut() {
+            let (shape, turns) = RoadShape::for_sides(network.sides(tile.0));
+
+            *texture = shape.texture(&textures);
+            transform.rotation = Quat::from_rotation_z(turns as f32 * std::f32::consts::FRAC_PI_2);
+        }
+    }
+}
+
+/// The road sprites, each drawn with its arms pointing north first
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoadShape {
+    Single,
+    End,
+    Straight,
+    Corner,
+    Junction,
+    Cross,
+}
+
+impl RoadShape {
+    const ALL: [RoadShape; 6] = [
+        RoadShape::Single,
+        RoadShape::End,
+        RoadShape::Straight,
+        RoadShape::Corner,
+        RoadShape::Junction,
+        RoadShape::Cross,
+    ];
+
+    /// Which sides the sprite connects to before it is rotated, north, east,
+    /// south then west
+    fn sides(&self) -> [bool; 4] {
+        match self {
+            RoadShape::Single => [false, false, false, false],
+            RoadShape::End => [true, false, false, false],
+            RoadShape::Straight => [true, false, true, false],
+            RoadShape::Corner => [true, true, false, false],
+            RoadShape::Junction => [true, true, true, false],
+            RoadShape::Cross => [true, true, true, true],
+        }
+    }
+
+    pub fn texture(&self, textures: &BuildingAssets) -> Handle<Image> {
+        match self {
+            RoadShape::Single => textures.road_single.clone(),
+            RoadShape::End => textures.road_end.clone(),
+            RoadShape::Straight => textures.road_straight.clone(),
+            RoadShape::Corner => textures.road_corner.clone(),
+            RoadShape::Junction => textures.road_junction.clone(),
+            RoadShape::Cross => textures.road_cross.clone(),
+        }
+    }
+
+    /// The shape and the number of anti-clockwise quarter turns that connect
+    /// a road to the given sides
+    pub fn for_sides(sides: [bool; 4]) -> (RoadShape, u32) {
+        for shape in RoadShape::ALL {
+            for turns in 0..4 {
+                let mut rotated = shape.sides();
+                // An anti-clockwise quarter turn moves north to west
+                rotated.rotate_left(turns);
+
+                if rotated == sides {
+                    return (shape, turns as u32);
+                }
+            }
+        }
+
+        (RoadShape::Single, 0)
+    }
+}
+
+/// Marks a building that has a road connection to the town centre
+#[derive(Component)]
+pub struct RoadAccess;
+
+impl RoadAccess {
+    /// Gives or takes `RoadAccess` from every building whenever roads or
+    /// buildings are added, disconnected buildings are greyed out. Buildings
+    /// whose entrance has no road turn to one that does.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn update(
+        mut commands: Commands,
+        network: Res<RoadNetwork>,
+        added_query: Query<(), Added<Building>>,
+        mut building_query: Query<
+            (
+                Entity,
+                &BuildingType,
+                &TilePosition,
+                &mut Facing,
+                &mut Sprite,
+                Option<&RoadAccess>,
+            ),
+            (With<Building>, Without<Road>, Without<TownCentre>),
+        >,
+    ) {
+        if !network.is_changed() && added_query.is_empty() {
+            return;
+        }
+
+        for (entity, building, tile, mut facing, mut sprite, access) in building_query.iter_mut() {
+            let turned = building.facing_for_access(&network, tile.0, *facing);
+            if turned != *facing {
+                *facing = turned;
+            }
+
+            let connected = network.has_access(building.entrance(tile.0, *facing));
+
+            // Keeps the alpha, blueprints are see-through
+            let mut tint = match connected {
+                true => Color::WHITE,
+                false => DISCONNECTED_TINT,
+            };
+            tint.set_a(sprite.color.a());
+            sprite.color = tint;
+
+            match (connected, access.is_some()) {
+                (true, false) => {
+                    commands.entity(entity).insert(RoadAccess);
+                }
+                (false, true) => {
+                    commands.entity(entity).remove::<RoadAccess>();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Every road tile and the roads next to it, plus which of them lead back to
+/// the town centre so access checks are a lookup
+#[derive(Resource, Default)]
+pub struct RoadNetwork {
+    graph: HashMap<IVec2, Vec<IVec2>>,
+    town_centre: Option<IVec2>,
+    connected: HashSet<IVec2>,
+}
+
+impl RoadNetwork {
+    pub fn is_road(&self, tile: IVec2) -> bool {
+        self.graph.contains_key(&tile)
+    }
+
+    /// The roads directly next to a road tile
+    pub fn neighbours(&self, tile: IVec2) -> &[IVec2] {
+        self.graph.get(&tile).map_or(&[], |neighbours| neighbours)
+    }
+
+    pub fn add_road(&mut self, tile: IVec2) {
+        if self.is_road(tile) {
+            return;
+        }
+
+        let neighbours = SIDES
+            .iter()
+            .map(|side| tile + *side)
+            .filter(|neighbour| self.is_road(*neighbour))
+            .collect::<Vec<_>>();
+
+        for neighbour in neighbours.iter() {
+            self.graph.entry(*neighbour).or_default().push(tile);
+        }
+        self.graph.insert(tile, neighbours);
+        self.rebuild_connected();
+    }
+
+    pub fn remove_road(&mut self, tile: IVec2) {
+        let Some(neighbours) = self.graph.remove(&tile) else {
+            return;
+        };
+
+        for neighbour in neighbours {
+            if let Some(edges) = self.graph.get_mut(&neighbour) {
+                edges.retain(|edge| *edge != tile);
+            }
+        }
+        self.rebuild_connected();
+    }
+
+    pub fn set_town_centre(&mut self, tile: Option<IVec2>) {
+        self.town_centre = tile;
+        self.rebuild_connected();
+    }
+
+    /// Whether the road leads back to the town centre
+    pub fn is_connected(&self, tile: IVec2) -> bool {
+        self.connected.contains(&tile)
+    }
+
+    /// A building with its entrance on `entrance` can reach the town centre,
+    /// either directly or by road
+    pub fn has_access(&self, entrance: IVec2) -> bool {
+        self.town_centre == Some(entrance) || self.is_connected(entrance)
+    }
+
+    /// Multiplier for the walking speed of a unit standing on `tile`
+    pub fn speed_multiplier(&self, tile: IVec2) -> f32 {
+        match self.is_road(tile) {
+            true => ROAD_SPEED_BONUS,
+            false => 1.0,
+        }
+    }
+
+    /// Which sides of `tile` have a road, north, east, south then west
+    fn sides(&self, tile: IVec2) -> [bool; 4] {
+        SIDES.map(|side| self.is_road(tile + side))
+    }
+
+    /// Walks the graph out from the roads touching the town centre
+    fn rebuild_connected(&mut self) {
+        self.connected.clear();
+
+        let Some(town_centre) = self.town_centre else {
+            return;
+        };
+
+        let mut queue = SIDES
+            .iter()
+            .map(|side| town_centre + *side)
+            .filter(|tile| self.is_road(*tile))
+            .collect::<VecDeque<_>>();
+
+        while let Some(tile) = queue.pop_front() {
+            if !self.connected.insert(tile) {
+                continue;
+            }
+
+            queue.extend(self.neighbours(tile).iter().copied());
+        }
+    }
+
+    /// Adds new roads and the town centre to the network
+    pub(super) fn track_buildings(
+        mut network: ResMut<RoadNetwork>,
+        building_query: Query<(&BuildingType, &TilePosition), Added<Building>>,
+    ) {
+        for (building, tile) in building_query.iter() {
+            match building {
+                BuildingType::Road => network.add_road(tile.0),
+                BuildingType::TownCentre => network.set_town_centre(Some(tile.0)),
+                _ => {}
+            }
+        }
+    }
+}
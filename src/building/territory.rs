@@ -0,0 +1,160 @@
+//! The area the player has claimed. The town centre claims the first circle
+//! of tiles, some buildings widen it, and nothing but the town centre can be
+//! built outside of it.
+
+use std::collections::HashSet;
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::BuildingType;
+use crate::world_gen::WorldMap;
+
+/// How wide the border line is, in pixels
+const BORDER_WIDTH: f32 = 3.0;
+
+const SIDES: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+#[derive(Resource, Default)]
+pub struct Territory {
+    tiles: HashSet<IVec2>,
+    /// The centre and radius of every claim, to rebuild the tiles when one goes
+    claims: Vec<(IVec2, i32)>,
+}
+
+impl Territory {
+    pub fn contains(&self, tile: IVec2) -> bool {
+        self.tiles.contains(&tile)
+    }
+
+    /// Claims every tile within `radius` of `centre`
+    pub fn claim(&mut self, centre: IVec2, radius: i32) {
+        self.claims.push((centre, radius));
+        self.add_circle(centre, radius);
+    }
+
+    /// Gives up a claim, tiles still covered by other claims stay
+    pub fn unclaim(&mut self, centre: IVec2, radius: i32) {
+        let Some(index) = self
+            .claims
+            .iter()
+            .position(|claim| *claim == (centre, radius))
+        else {
+            return;
+        };
+
+        self.claims.remove(index);
+        self.tiles.clear();
+        for (centre, radius) in self.claims.clone() {
+            self.add_circle(centre, radius);
+        }
+    }
+
+    fn add_circle(&mut self, centre: IVec2, radius: i32) {
+        for x in -radius..=radius {
+            for y in -radius..=radius {
+                if x * x + y * y <= radius * radius {
+                    self.tiles.insert(centre + IVec2::new(x, y));
+                }
+            }
+        }
+    }
+}
+
+impl BuildingType {
+    /// How far around itself the building claims territory
+    pub fn territory_radius(&self) -> Option<i32> {
+        match self {
+            BuildingType::TownCentre => Some(8),
+            BuildingType::Barracks => Some(5),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub(super) struct TerritoryBorder;
+
+impl TerritoryBorder {
+    /// Redraws the border whenever the territory grows
+    pub(super) fn redraw(
+        mut commands: Commands,
+        territory: Res<Territory>,
+        map: Option<Res<WorldMap>>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        border_query: Query<Entity, With<TerritoryBorder>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        if !territory.is_changed() {
+            return;
+        }
+
+        let mesh = Mesh2dHandle(meshes.add(TerritoryBorder::build_mesh(&territory, &map)));
+
+        match border_query.get_single() {
+            Ok(entity) => {
+                commands.entity(entity).insert(mesh);
+            }
+            Err(_) => {
+                commands.spawn((
+                    TerritoryBorder,
+                    MaterialMesh2dBundle {
+                        mesh,
+                        material: materials.add(ColorMaterial::from(Color::rgba(1., 1., 1., 0.35))),
+                        transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                        ..default()
+                    },
+                    Name::new("TerritoryBorder"),
+                ));
+            }
+        }
+    }
+
+    /// A thin strip along every side of a claimed tile that faces unclaimed land
+    fn build_mesh(territory: &Territory, map: &WorldMap) -> Mesh {
+        let half = map.block_size / 2.;
+
+        let mut positions: Vec<[f32; 3]> = vec![];
+        let mut indices: Vec<u32> = vec![];
+
+        for tile in territory.tiles.iter() {
+            let centre = map.tile_to_world(*tile);
+
+            for side in SIDES {
+                if territory.contains(*tile + side) {
+                    continue;
+                }
+
+                let side = side.as_vec2();
+                let along = side.perp();
+                let outer = centre + side * half;
+                let inner = centre + side * (half - BORDER_WIDTH);
+                let first = positions.len() as u32;
+
+                positions.extend([
+                    [outer.x - along.x * half, outer.y - along.y * half, 0.0],
+                    [outer.x + along.x * half, outer.y + along.y * half, 0.0],
+                    [inner.x + along.x * half, inner.y + along.y * half, 0.0],
+                    [inner.x - along.x * half, inner.y - along.y * half, 0.0],
+                ]);
+                indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+            }
+        }
+
+        let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+        let uvs = vec![[0.0, 0.0]; positions.len()];
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
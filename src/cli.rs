@@ -0,0 +1,99 @@
+//! Command line flags, mostly for jumping straight into a game while developing
+//! or reproducing a bug.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{global_state::GlobalState, save::SaveGame};
+
+pub const USAGE: &str = "Usage: game_growth [options]
+
+Options:
+    --seed <number>         World seed, random when left out
+    --width <tiles>         Number of columns
+    --height <tiles>        Number of rows
+    --block-size <pixels>   Size of a tile
+    --load <savefile>       Load a save, implies --skip-menu. The save has its
+                            own world, so it can't be combined with --seed,
+                            --width, --height or --block-size
+    --skip-menu             Start playing as soon as the assets are loaded";
+
+/// What was asked for on the command line, beyond the `GlobalState` settings
+#[derive(Resource, Default)]
+pub struct LaunchOptions {
+    /// Go straight from loading into `GameState::Playing`
+    pub skip_menu: bool,
+    /// Seed given on the command line, pre-filled in the menu
+    pub seed: Option<u32>,
+    /// Save loaded on the command line, its buildings are placed once the world exists
+    pub load: Option<(PathBuf, SaveGame)>,
+}
+
+impl LaunchOptions {
+    /// Parses the flags (without the program name) into the starting
+    /// `GlobalState` and the remaining launch options
+    pub fn from_args(
+        mut args: impl Iterator<Item = String>,
+    ) -> Result<(GlobalState, LaunchOptions), String> {
+        let mut global_state = GlobalState::default();
+        let mut options = LaunchOptions::default();
+        // A save brings its own world, so these can't go with --load
+        let mut world_flag = None;
+
+        while let Some(flag) = args.next() {
+            if matches!(
+                flag.as_str(),
+                "--seed" | "--width" | "--height" | "--block-size"
+            ) {
+                world_flag = Some(flag.clone());
+            }
+
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("{} is missing a value", flag))
+            };
+
+            match flag.as_str() {
+                "--seed" => {
+                    let seed = parse_number(&flag, value()?)?;
+                    global_state.seed = seed;
+                    options.seed = Some(seed);
+                }
+                "--width" => global_state.world_cols = parse_number(&flag, value()?)?,
+                "--height" => global_state.world_rows = parse_number(&flag, value()?)?,
+                "--block-size" => global_state.block_size = parse_number(&flag, value()?)?,
+                "--load" => {
+                    let path = PathBuf::from(value()?);
+                    let save = SaveGame::load(&path)?;
+                    options.load = Some((path, save));
+                }
+                "--skip-menu" => options.skip_menu = true,
+                other => return Err(format!("unknown option {}", other)),
+            }
+        }
+
+        if let Some((_, save)) = &options.load {
+            if let Some(flag) = world_flag {
+                return Err(format!("{} can't be combined with --load", flag));
+            }
+            global_state = save.global_state.clone();
+            options.skip_menu = true;
+        }
+
+        if global_state.world_cols == 0 || global_state.world_rows == 0 {
+            return Err("the map needs at least one column and one row".to_string());
+        }
+        if global_state.block_size == 0 {
+            return Err("the block size has to be at least one pixel".to_string());
+        }
+
+        Ok((global_state, options))
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got {}", flag, value))
+}
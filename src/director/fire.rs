@@ -0,0 +1,207 @@
+//! Fires. Every burning tile can set the tiles next to it alight when they
+//! hold a building or a tree, buildings lose health while they burn and trees
+//! are gone once they have burnt out.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::{seq::IteratorRandom, Rng};
+
+use crate::{
+    building::{Buildable, Building, BuildingType, Facing, RoadNetwork},
+    tiles::{ResourceKind, ResourceTile, ResourceTree, TilePosition},
+    units::Health,
+    world_gen::WorldMap,
+};
+
+/// Seconds a tile burns before the fire on it is out
+const BURN_SECONDS: f32 = 12.0;
+/// Seconds between the fire spreading and burning buildings taking damage
+const SPREAD_SECONDS: f32 = 3.0;
+/// Chance of a burning tile setting each of its neighbours alight
+const SPREAD_CHANCE: f64 = 0.35;
+/// Health a burning building loses every time the fire spreads
+const BURN_DAMAGE: u32 = 20;
+const FLAME_COLOUR: Color = Color::rgba(1.0, 0.45, 0.1, 0.6);
+
+/// Drawn over a burning tile
+#[derive(Component)]
+pub struct Burning(pub IVec2);
+
+/// Every tile on fire and how long it has left to burn
+#[derive(Resource)]
+pub struct Wildfire {
+    burning: HashMap<IVec2, Timer>,
+    spread: Timer,
+}
+
+impl Default for Wildfire {
+    fn default() -> Self {
+        Self {
+            burning: HashMap::new(),
+            spread: Timer::from_seconds(SPREAD_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Wildfire {
+    pub fn is_burning(&self, tile: IVec2) -> bool {
+        self.burning.contains_key(&tile)
+    }
+
+    pub fn is_out(&self) -> bool {
+        self.burning.is_empty()
+    }
+
+    /// Sets `tile` alight, or keeps it burning for longer when it already is
+    pub fn ignite(&mut self, tile: IVec2) {
+        self.burning
+            .insert(tile, Timer::from_seconds(BURN_SECONDS, TimerMode::Once));
+    }
+
+    /// Buildings and trees burn, roads don't
+    fn is_flammable(map: &WorldMap, network: &RoadNetwork, tile: IVec2) -> bool {
+        map.get(tile).is_some_and(|map_tile| {
+            (map_tile.occupied && !network.is_road(tile))
+                || map_tile.resource == Some(ResourceKind::Tree)
+        })
+    }
+
+    /// Sets a random building alight, returns where the fire started
+    pub(super) fn ignite_random(
+        &mut self,
+        building_query: &Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+    ) -> Option<IVec2> {
+        let tile = building_query
+            .iter()
+            .filter(|(building, ..)| **building != BuildingType::Road)
+            .flat_map(|(building, tile, facing)| building.tiles(tile.0, *facing))
+            .choose(&mut rand::thread_rng())?;
+
+        self.ignite(tile);
+        Some(tile)
+    }
+
+    /// Every few seconds fire jumps to neighbouring buildings and trees, and
+    /// buildings with a burning tile take damage
+    pub(super) fn spread(
+        time: Res<Time>,
+        mut wildfire: ResMut<Wildfire>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        mut building_query: Query<
+            (&BuildingType, &TilePosition, &Facing, &mut Health),
+            With<Building>,
+        >,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        if wildfire.is_out() || !wildfire.spread.tick(time.delta()).just_finished() {
+            return;
+        }
+        let mut rng = rand::thread_rng();
+
+        let caught = wildfire
+            .burning
+            .keys()
+            .flat_map(|tile| {
+                [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|step| *tile + step)
+            })
+            .filter(|tile| !wildfire.is_burning(*tile))
+            .filter(|tile| Wildfire::is_flammable(&map, &network, *tile))
+            .filter(|_| rng.gen_bool(SPREAD_CHANCE))
+            .collect::<Vec<_>>();
+        for tile in caught {
+            wildfire.ignite(tile);
+        }
+
+        for (building, tile, facing, mut health) in building_query.iter_mut() {
+            let on_fire = building
+                .tiles(tile.0, *facing)
+                .iter()
+                .any(|tile| wildfire.is_burning(*tile));
+
+            if on_fire && health.current > 0 {
+                health.damage(BURN_DAMAGE);
+            }
+        }
+    }
+
+    /// Puts out tiles that have burnt for long enough or have nothing left to
+    /// burn, trees that burnt out are gone. Keeps a flame over every tile
+    /// that is still burning.
+    pub(super) fn burn(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut wildfire: ResMut<Wildfire>,
+        map: Option<ResMut<WorldMap>>,
+        network: Res<RoadNetwork>,
+        tree_query: Query<(Entity, &TilePosition), With<ResourceTree>>,
+        flame_query: Query<(Entity, &Burning)>,
+    ) {
+        let Some(mut map) = map else {
+            return;
+        };
+
+        let mut burnt_out = vec![];
+        for (tile, timer) in wildfire.burning.iter_mut() {
+            let finished = timer.tick(time.delta()).finished();
+
+            if finished || !Wildfire::is_flammable(&map, &network, *tile) {
+                burnt_out.push(*tile);
+            }
+        }
+
+        for tile in burnt_out {
+            wildfire.burning.remove(&tile);
+
+            let Some(map_tile) = map.get_mut(tile) else {
+                continue;
+            };
+            if map_tile.resource != Some(ResourceKind::Tree) {
+                continue;
+            }
+
+            map_tile.resource = None;
+            map_tile.resource_lvl = 0;
+            let buildable = map_tile.is_buildable();
+            map.mark_dirty(WorldMap::chunk_of(tile));
+
+            for (entity, _) in tree_query.iter().filter(|(_, position)| position.0 == tile) {
+                let mut entity = commands.entity(entity);
+                entity.remove::<(ResourceTile, ResourceTree)>();
+                if buildable {
+                    entity.insert(Buildable);
+                }
+            }
+        }
+
+        for (entity, Burning(tile)) in flame_query.iter() {
+            if !wildfire.is_burning(*tile) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        for tile in wildfire.burning.keys() {
+            if flame_query.iter().any(|(_, Burning(flame))| flame == tile) {
+                continue;
+            }
+
+            let position = map.tile_to_world(*tile);
+            commands.spawn((
+                Burning(*tile),
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: FLAME_COLOUR,
+                        custom_size: Some(Vec2::splat(map.block_size * 0.8)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(position.x, position.y, 4.0),
+                    ..default()
+                },
+                Name::new("Fire"),
+            ));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Berry bushes that have been picked down grow back over time, except
+//! during a drought.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{EventDirector, RandomEvent};
+use crate::{
+    tiles::{ResourceBerry, ResourceKind, TilePosition},
+    world_gen::WorldMap,
+};
+
+/// Seconds between bushes growing
+const REGROW_SECONDS: f32 = 30.0;
+/// Chance of each bush below its full size growing a level
+const REGROW_CHANCE: f64 = 0.5;
+/// The biggest a bush grows
+const MAX_BERRY_LVL: usize = 2;
+
+pub(super) fn regrow_berries(
+    time: Res<Time>,
+    mut since_last: Local<f32>,
+    director: Res<EventDirector>,
+    map: Option<ResMut<WorldMap>>,
+    mut berry_query: Query<(&mut ResourceBerry, &TilePosition)>,
+) {
+    let Some(mut map) = map else {
+        return;
+    };
+    if director.is_active(RandomEvent::Drought) {
+        return;
+    }
+
+    *since_last += time.delta_seconds();
+    if *since_last < REGROW_SECONDS {
+        return;
+    }
+    *since_last = 0.0;
+
+    let mut rng = rand::thread_rng();
+    let tiles = map
+        .chunk_coords()
+        .copied()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flat_map(WorldMap::chunk_tiles)
+        .collect::<Vec<_>>();
+
+    for tile in tiles {
+        let Some(map_tile) = map.get_mut(tile) else {
+            continue;
+        };
+        let growing =
+            map_tile.resource == Some(ResourceKind::Berry) && map_tile.resource_lvl < MAX_BERRY_LVL;
+        if !growing || !rng.gen_bool(REGROW_CHANCE) {
+            continue;
+        }
+
+        map_tile.resource_lvl += 1;
+        let lvl = map_tile.resource_lvl;
+        map.mark_dirty(WorldMap::chunk_of(tile));
+
+        for (mut berry, _) in berry_query
+            .iter_mut()
+            .filter(|(_, position)| position.0 == tile)
+        {
+            berry.lvl = lvl;
+        }
+    }
+}
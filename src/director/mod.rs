@@ -0,0 +1,287 @@
+//! Random events. Once the town centre stands, the director rolls a weighted
+//! table at the start of every game day. Most events last a few days, a fire
+//! lasts until the last flame has burnt out.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    building::{Building, BuildingType, Facing, TownCentre},
+    loading::PersonAssets,
+    notifications::{Notification, Severity},
+    tiles::TilePosition,
+    units::Worker,
+    world_gen::WorldMap,
+    GameState,
+};
+
+mod fire;
+mod growth;
+
+pub use fire::{Burning, Wildfire};
+
+/// Seconds in one game day
+pub const DAY_SECONDS: f32 = 120.0;
+/// Weight of a day without any event, against the weights of the events
+const QUIET_DAY_WEIGHT: u32 = 4;
+/// Workers that arrive with a migrant wave
+const MIGRANTS: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RandomEvent {
+    Fire,
+    Drought,
+    MigrantWave,
+    GoodHarvest,
+}
+
+impl RandomEvent {
+    pub const ALL: [RandomEvent; 4] = [
+        RandomEvent::Fire,
+        RandomEvent::Drought,
+        RandomEvent::MigrantWave,
+        RandomEvent::GoodHarvest,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RandomEvent::Fire => "Fire",
+            RandomEvent::Drought => "Drought",
+            RandomEvent::MigrantWave => "Migrants",
+            RandomEvent::GoodHarvest => "Good harvest",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RandomEvent::Fire => "A fire broke out, it spreads to buildings and trees next to it",
+            RandomEvent::Drought => "The berries have stopped growing back",
+            RandomEvent::MigrantWave => "Migrants arrived at the town centre looking for work",
+            RandomEvent::GoodHarvest => "Farms bring in twice as much food",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            RandomEvent::Fire => Severity::Danger,
+            RandomEvent::Drought => Severity::Warning,
+            RandomEvent::MigrantWave | RandomEvent::GoodHarvest => Severity::Good,
+        }
+    }
+
+    /// How likely the event is to be rolled, against the other events
+    pub fn weight(&self) -> u32 {
+        match self {
+            RandomEvent::Fire => 2,
+            RandomEvent::Drought => 2,
+            RandomEvent::MigrantWave => 3,
+            RandomEvent::GoodHarvest => 3,
+        }
+    }
+
+    /// Game days the event's effects last, fires burn out on their own and
+    /// migrants stay
+    fn days(&self) -> Option<f32> {
+        match self {
+            RandomEvent::Drought => Some(2.0),
+            RandomEvent::GoodHarvest => Some(1.5),
+            RandomEvent::Fire | RandomEvent::MigrantWave => None,
+        }
+    }
+
+    /// Picks the day's event from the weighted table, none on a quiet day
+    pub fn roll(rng: &mut impl Rng) -> Option<RandomEvent> {
+        let total = QUIET_DAY_WEIGHT + RandomEvent::ALL.iter().map(|e| e.weight()).sum::<u32>();
+        let mut roll = rng.gen_range(0..total);
+
+        for event in RandomEvent::ALL {
+            if roll < event.weight() {
+                return Some(event);
+            }
+            roll -= event.weight();
+        }
+
+        None
+    }
+}
+
+/// Sent when an event starts, with the tile it started on when it has one
+pub struct RandomEventStarted {
+    pub event: RandomEvent,
+    pub day: u32,
+    pub tile: Option<IVec2>,
+}
+
+/// Sent when an event's effects have worn off
+pub struct RandomEventEnded {
+    pub event: RandomEvent,
+}
+
+#[derive(Resource)]
+pub struct EventDirector {
+    /// Game days since the town centre went up
+    pub day: u32,
+    clock: Timer,
+    /// Events to start on the coming frame
+    pending: Vec<RandomEvent>,
+    /// Events whose effects are still going, and for how much longer
+    active: Vec<(RandomEvent, Option<Timer>)>,
+}
+
+impl Default for EventDirector {
+    fn default() -> Self {
+        Self {
+            day: 0,
+            clock: Timer::from_seconds(DAY_SECONDS, TimerMode::Repeating),
+            pending: vec![],
+            active: vec![],
+        }
+    }
+}
+
+impl EventDirector {
+    pub fn is_active(&self, event: RandomEvent) -> bool {
+        self.active.iter().any(|(active, _)| *active == event)
+    }
+
+    pub fn seconds_until_next_day(&self) -> f32 {
+        self.clock.remaining_secs()
+    }
+
+    /// Starts `event` on the coming frame instead of waiting for it to be rolled
+    pub fn start(&mut self, event: RandomEvent) {
+        self.pending.push(event);
+    }
+
+    /// Counts the days while the town centre stands and rolls an event at the
+    /// start of each. Rolling an event that is still going makes for a quiet
+    /// day instead.
+    fn roll_daily(
+        time: Res<Time>,
+        mut director: ResMut<EventDirector>,
+        town_centre_query: Query<(), With<TownCentre>>,
+    ) {
+        if town_centre_query.is_empty() || !director.clock.tick(time.delta()).just_finished() {
+            return;
+        }
+
+        director.day += 1;
+        let event = RandomEvent::roll(&mut rand::thread_rng());
+        if let Some(event) = event.filter(|event| !director.is_active(*event)) {
+            director.pending.push(event);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn start_pending(
+        mut commands: Commands,
+        mut director: ResMut<EventDirector>,
+        mut wildfire: ResMut<Wildfire>,
+        map: Option<Res<WorldMap>>,
+        textures: Res<PersonAssets>,
+        town_centre_query: Query<&TilePosition, With<TownCentre>>,
+        building_query: Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+        mut started: EventWriter<RandomEventStarted>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for event in std::mem::take(&mut director.pending) {
+            let tile = match event {
+                RandomEvent::Fire => {
+                    let Some(tile) = wildfire.ignite_random(&building_query) else {
+                        continue;
+                    };
+                    Some(tile)
+                }
+                RandomEvent::MigrantWave => {
+                    let Ok(town_centre) = town_centre_query.get_single() else {
+                        continue;
+                    };
+                    for _ in 0..MIGRANTS {
+                        Worker::spawn(&mut commands, &textures, &map, town_centre.0);
+                    }
+                    Some(town_centre.0)
+                }
+                RandomEvent::Drought | RandomEvent::GoodHarvest => None,
+            };
+
+            // Starting an event that is already going only makes it last longer
+            if event != RandomEvent::MigrantWave {
+                director.active.retain(|(active, _)| *active != event);
+                let lasts = event
+                    .days()
+                    .map(|days| Timer::from_seconds(days * DAY_SECONDS, TimerMode::Once));
+                director.active.push((event, lasts));
+            }
+
+            started.send(RandomEventStarted {
+                event,
+                day: director.day,
+                tile,
+            });
+
+            let notification = Notification::new(
+                event.severity(),
+                format!("{}: {}", event.label(), event.description()),
+            );
+            notifications.send(match tile {
+                Some(tile) => notification.at(tile),
+                None => notification,
+            });
+        }
+    }
+
+    /// Ends events once their days are up, and a fire once nothing burns
+    fn expire(
+        time: Res<Time>,
+        mut director: ResMut<EventDirector>,
+        wildfire: Res<Wildfire>,
+        mut ended: EventWriter<RandomEventEnded>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        let mut over = vec![];
+
+        for (event, lasts) in director.active.iter_mut() {
+            let done = match lasts {
+                Some(timer) => timer.tick(time.delta()).finished(),
+                None => wildfire.is_out(),
+            };
+
+            if done {
+                over.push(*event);
+            }
+        }
+
+        director.active.retain(|(event, _)| !over.contains(event));
+        for event in over {
+            ended.send(RandomEventEnded { event });
+            notifications.send(Notification::new(
+                Severity::Info,
+                format!("{} is over", event.label()),
+            ));
+        }
+    }
+}
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RandomEventStarted>()
+            .add_event::<RandomEventEnded>()
+            .init_resource::<EventDirector>()
+            .init_resource::<Wildfire>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(EventDirector::roll_daily)
+                    .with_system(EventDirector::start_pending.after(EventDirector::roll_daily))
+                    .with_system(Wildfire::spread.after(EventDirector::start_pending))
+                    .with_system(Wildfire::burn.after(Wildfire::spread))
+                    .with_system(EventDirector::expire.after(Wildfire::burn))
+                    .with_system(growth::regrow_berries),
+            );
+    }
+}
@@ -0,0 +1,157 @@
+//! The market at the stall. A staffed stall sells off whatever the colony has
+//! too much of and lets the player buy what it is short of, at prices that
+//! follow how much of each good is in the stockpile.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::{BatchFinished, Good, Production, Stockpile};
+use crate::building::{Blueprint, BuildingType, RoadAccess};
+
+/// Goods bought with one click of a buy button
+pub const BUY_AMOUNT: u32 = 10;
+/// Most of a good the stall sells after one batch
+const SALE_AMOUNT: u32 = 10;
+/// The stall only sells what the stockpile holds beyond this
+const SURPLUS: u32 = 150;
+/// Supply at which a good sells for its base price
+const NORMAL_SUPPLY: f32 = 100.0;
+/// How far prices can fall below or rise above the base price
+const PRICE_RANGE: f32 = 4.0;
+/// Part of the price the stall gets when selling
+const SELL_SHARE: f32 = 0.8;
+
+impl Good {
+    /// Coin one unit is worth when the colony holds a normal amount of it
+    fn base_price(&self) -> f32 {
+        match self {
+            Good::Wood => 1.0,
+            Good::Stone => 1.5,
+            Good::Food => 1.0,
+            Good::Planks => 4.0,
+            Good::Blocks => 5.0,
+            Good::Coin => 1.0,
+        }
+    }
+
+    /// Can be bought and sold at the market
+    pub fn is_traded(&self) -> bool {
+        *self != Good::Coin
+    }
+}
+
+/// Asks the market for `amount` of `good`, ignored while it is closed or the
+/// stockpile can't pay
+pub struct BuyGoods {
+    pub good: Good,
+    pub amount: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct Market {
+    open: bool,
+    /// Coin for one unit of each traded good
+    prices: HashMap<Good, f32>,
+}
+
+impl Market {
+    /// A stall is finished, staffed and has a road to the town centre
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// The price of one unit when the colony holds `supply` of it, goods get
+    /// dearer the scarcer they are
+    pub fn price_for_supply(good: Good, supply: u32) -> f32 {
+        let scarcity = (NORMAL_SUPPLY / supply.max(1) as f32).sqrt();
+
+        good.base_price() * scarcity.clamp(1.0 / PRICE_RANGE, PRICE_RANGE)
+    }
+
+    pub fn price(&self, good: Good) -> f32 {
+        self.prices
+            .get(&good)
+            .copied()
+            .unwrap_or_else(|| good.base_price())
+    }
+
+    /// Coin it takes to buy `amount` of `good`
+    pub fn buy_cost(&self, good: Good, amount: u32) -> u32 {
+        (self.price(good) * amount as f32).ceil() as u32
+    }
+
+    /// Coin the stall gets for selling `amount` of `good`
+    pub fn sale_value(&self, good: Good, amount: u32) -> u32 {
+        (self.price(good) * SELL_SHARE * amount as f32).floor() as u32
+    }
+
+    pub(super) fn update_prices(stockpile: Res<Stockpile>, mut market: ResMut<Market>) {
+        if !stockpile.is_changed() && !market.prices.is_empty() {
+            return;
+        }
+
+        market.prices = Good::ALL
+            .into_iter()
+            .filter(Good::is_traded)
+            .map(|good| (good, Market::price_for_supply(good, stockpile.amount(good))))
+            .collect();
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(super) fn update_open(
+        mut market: ResMut<Market>,
+        stall_query: Query<(&BuildingType, &Production), (With<RoadAccess>, Without<Blueprint>)>,
+    ) {
+        let open = stall_query.iter().any(|(building, production)| {
+            *building == BuildingType::Stall && production.is_staffed()
+        });
+
+        if market.open != open {
+            market.open = open;
+        }
+    }
+
+    /// Every batch at a stall sells some of the good the colony has the
+    /// biggest surplus of
+    pub(super) fn sell_surplus(
+        mut events: EventReader<BatchFinished>,
+        market: Res<Market>,
+        mut stockpile: ResMut<Stockpile>,
+    ) {
+        for event in events.iter() {
+            if event.building != BuildingType::Stall {
+                continue;
+            }
+
+            let surplus = Good::ALL
+                .into_iter()
+                .filter(Good::is_traded)
+                .map(|good| (good, stockpile.amount(good).saturating_sub(SURPLUS)))
+                .max_by_key(|(_, surplus)| *surplus);
+
+            let Some((good, surplus)) = surplus.filter(|(_, surplus)| *surplus > 0) else {
+                continue;
+            };
+
+            let sold = stockpile.take(good, surplus.min(SALE_AMOUNT));
+            stockpile.add(&[(Good::Coin, market.sale_value(good, sold))]);
+        }
+    }
+
+    pub(super) fn buy(
+        mut events: EventReader<BuyGoods>,
+        market: Res<Market>,
+        mut stockpile: ResMut<Stockpile>,
+    ) {
+        for BuyGoods { good, amount } in events.iter() {
+            if !market.open || !good.is_traded() {
+                continue;
+            }
+
+            if stockpile.spend(&[(Good::Coin, market.buy_cost(*good, *amount))]) {
+                stockpile.add(&[(*good, *amount)]);
+            }
+        }
+    }
+}
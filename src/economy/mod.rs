@@ -0,0 +1,230 @@
+//! Goods the colony owns and what buildings cost.
+
+use std::{collections::HashMap, str::FromStr};
+
+use bevy::prelude::*;
+
+use crate::{
+    building::BuildingType,
+    notifications::{Notification, Severity},
+    GameState,
+};
+
+mod market;
+mod production;
+
+pub use market::{BuyGoods, Market, BUY_AMOUNT};
+pub use production::{BatchFinished, Production, Recipe};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Good {
+    Wood,
+    Stone,
+    Food,
+    Planks,
+    Blocks,
+    Coin,
+}
+
+impl Good {
+    pub const ALL: [Good; 6] = [
+        Good::Wood,
+        Good::Stone,
+        Good::Food,
+        Good::Planks,
+        Good::Blocks,
+        Good::Coin,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Good::Wood => "Wood",
+            Good::Stone => "Stone",
+            Good::Food => "Food",
+            Good::Planks => "Planks",
+            Good::Blocks => "Blocks",
+            Good::Coin => "Coin",
+        }
+    }
+
+    /// What a new colony starts with
+    fn starting_amount(&self) -> u32 {
+        match self {
+            Good::Wood => 150,
+            Good::Stone => 60,
+            Good::Food => 100,
+            Good::Planks | Good::Blocks | Good::Coin => 0,
+        }
+    }
+}
+
+impl FromStr for Good {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        Good::ALL
+            .into_iter()
+            .find(|good| good.label().eq_ignore_ascii_case(label))
+            .ok_or_else(|| format!("unknown good {}", label))
+    }
+}
+
+/// Food below which the colony is warned that it is running short
+const FOOD_SHORTAGE: u32 = 20;
+
+/// Amounts of each good, what the stockpile holds or what something costs
+pub type Goods = [(Good, u32)];
+
+/// Everything the colony has stored
+#[derive(Resource, Clone)]
+pub struct Stockpile {
+    amounts: HashMap<Good, u32>,
+}
+
+impl Default for Stockpile {
+    fn default() -> Self {
+        Self {
+            amounts: Good::ALL
+                .into_iter()
+                .map(|good| (good, good.starting_amount()))
+                .collect(),
+        }
+    }
+}
+
+impl Stockpile {
+    pub fn amount(&self, good: Good) -> u32 {
+        self.amounts.get(&good).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, good: Good, amount: u32) {
+        self.amounts.insert(good, amount);
+    }
+
+    pub fn add(&mut self, goods: &Goods) {
+        for (good, amount) in goods {
+            *self.amounts.entry(*good).or_default() += amount;
+        }
+    }
+
+    /// Takes out up to `amount` of `good`, returns how much there was to take
+    pub fn take(&mut self, good: Good, amount: u32) -> u32 {
+        let taken = amount.min(self.amount(good));
+        if taken > 0 {
+            self.set(good, self.amount(good) - taken);
+        }
+
+        taken
+    }
+
+    pub fn can_afford(&self, goods: &Goods) -> bool {
+        goods
+            .iter()
+            .all(|(good, amount)| self.amount(*good) >= *amount)
+    }
+
+    /// Warns once when food runs low, and only again after it has recovered
+    fn warn_of_shortage(
+        stockpile: Res<Stockpile>,
+        mut warned: Local<bool>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        if !stockpile.is_changed() {
+            return;
+        }
+
+        let short = stockpile.amount(Good::Food) < FOOD_SHORTAGE;
+        if short && !*warned {
+            notifications.send(Notification::new(
+                Severity::Warning,
+                "Food is running short, farms need workers",
+            ));
+        }
+        *warned = short;
+    }
+
+    /// Takes `goods` out of the stockpile, or leaves it untouched and returns
+    /// false when there isn't enough
+    pub fn spend(&mut self, goods: &Goods) -> bool {
+        if !self.can_afford(goods) {
+            return false;
+        }
+
+        for (good, amount) in goods {
+            *self.amounts.entry(*good).or_default() -= amount;
+        }
+
+        true
+    }
+}
+
+impl BuildingType {
+    pub fn cost(&self) -> &'static Goods {
+        match self {
+            BuildingType::TownCentre => &[],
+            BuildingType::Barracks => &[(Good::Wood, 40), (Good::Stone, 30)],
+            BuildingType::Farm => &[(Good::Wood, 15)],
+            BuildingType::HouseOne | BuildingType::HouseTwo | BuildingType::House => {
+                &[(Good::Wood, 20)]
+            }
+            BuildingType::Shade => &[(Good::Wood, 10)],
+            BuildingType::Stall => &[(Good::Wood, 25), (Good::Stone, 5)],
+            BuildingType::Road => &[(Good::Stone, 1)],
+            BuildingType::LumberMill => &[(Good::Wood, 30), (Good::Stone, 10)],
+            BuildingType::Stonecutter => &[(Good::Wood, 30), (Good::Stone, 15)],
+        }
+    }
+
+    /// What the building turns into when upgraded and what that costs.
+    /// Upgrades need goods from the lumber mill and stonecutter.
+    pub fn upgrade(&self) -> Option<(BuildingType, &'static Goods)> {
+        match self {
+            BuildingType::House => Some((
+                BuildingType::HouseOne,
+                &[(Good::Wood, 10), (Good::Planks, 10)],
+            )),
+            BuildingType::HouseOne => Some((
+                BuildingType::HouseTwo,
+                &[(Good::Planks, 20), (Good::Blocks, 10)],
+            )),
+            _ => None,
+        }
+    }
+
+    /// What the building was before it was upgraded
+    pub fn downgrade(&self) -> Option<BuildingType> {
+        BuildingType::ALL.into_iter().find(|building| {
+            building
+                .upgrade()
+                .is_some_and(|(upgrade, _)| upgrade == *self)
+        })
+    }
+}
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BatchFinished>()
+            .add_event::<BuyGoods>()
+            .init_resource::<Stockpile>()
+            .init_resource::<Market>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(Production::track_buildings)
+                    .with_system(Production::hire_workers.after(Production::track_buildings))
+                    .with_system(Production::work.after(Production::hire_workers))
+                    .with_system(Production::haul.after(Production::work))
+                    .with_system(Market::update_open.after(Production::work))
+                    .with_system(Market::sell_surplus.after(Production::work))
+                    .with_system(Market::buy.after(Market::update_open))
+                    .with_system(Stockpile::warn_of_shortage.after(Production::haul))
+                    .with_system(
+                        Market::update_prices
+                            .after(Production::haul)
+                            .after(Market::sell_surplus)
+                            .after(Market::buy),
+                    ),
+            );
+    }
+}
@@ -0,0 +1,293 @@
+//! Buildings that turn goods into other goods. Each one keeps its own input
+//! and output buffers, carts move goods between them and the stockpile, and a
+//! batch only runs while the building's workers are there.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::{Good, Goods, Stockpile};
+use crate::{
+    building::{Blueprint, BuildingType, Facing, RoadAccess, RoadNetwork},
+    director::{EventDirector, RandomEvent},
+    tiles::TilePosition,
+    units::{Walker, Worker},
+    world_gen::WorldMap,
+};
+
+/// How many batches' worth of goods each buffer holds
+const BUFFER_BATCHES: u32 = 3;
+/// Seconds between carts coming by to fill and empty the buffers
+const HAUL_SECONDS: f32 = 2.0;
+
+/// Sent whenever a building finishes a batch
+pub struct BatchFinished {
+    pub entity: Entity,
+    pub building: BuildingType,
+}
+
+/// What a building makes out of what, and how fast
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Recipe {
+    pub inputs: &'static Goods,
+    pub outputs: &'static Goods,
+    /// Seconds one batch takes
+    pub cycle_seconds: f32,
+    /// Workers that have to be at the building for it to run
+    pub workers: usize,
+}
+
+impl BuildingType {
+    pub fn recipe(&self) -> Option<Recipe> {
+        match self {
+            BuildingType::Farm => Some(Recipe {
+                inputs: &[],
+                outputs: &[(Good::Food, 3)],
+                cycle_seconds: 10.0,
+                workers: 1,
+            }),
+            BuildingType::LumberMill => Some(Recipe {
+                inputs: &[(Good::Wood, 2)],
+                outputs: &[(Good::Planks, 1)],
+                cycle_seconds: 6.0,
+                workers: 2,
+            }),
+            BuildingType::Stonecutter => Some(Recipe {
+                inputs: &[(Good::Stone, 2)],
+                outputs: &[(Good::Blocks, 1)],
+                cycle_seconds: 8.0,
+                workers: 2,
+            }),
+            // Trades at the market after every batch instead of making anything
+            BuildingType::Stall => Some(Recipe {
+                inputs: &[],
+                outputs: &[],
+                cycle_seconds: 5.0,
+                workers: 1,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Production {
+    pub recipe: Recipe,
+    /// Goods brought in and waiting to be used
+    pub input: HashMap<Good, u32>,
+    /// Goods made and waiting to be taken to the stockpile
+    pub output: HashMap<Good, u32>,
+    cycle: Timer,
+    staffed: bool,
+}
+
+impl Production {
+    pub fn new(recipe: Recipe) -> Self {
+        Self {
+            recipe,
+            input: HashMap::new(),
+            output: HashMap::new(),
+            cycle: Timer::from_seconds(recipe.cycle_seconds, TimerMode::Repeating),
+            staffed: false,
+        }
+    }
+
+    /// All of its workers are at the building
+    pub fn is_staffed(&self) -> bool {
+        self.staffed
+    }
+
+    fn buffered(buffer: &HashMap<Good, u32>, good: Good) -> u32 {
+        buffer.get(&good).copied().unwrap_or(0)
+    }
+
+    /// Everything waiting in its input and output buffers
+    pub fn buffered_goods(&self) -> Vec<(Good, u32)> {
+        self.input
+            .iter()
+            .chain(self.output.iter())
+            .filter(|(_, amount)| **amount > 0)
+            .map(|(good, amount)| (*good, *amount))
+            .collect()
+    }
+
+    /// Has the inputs for a batch and room for what it makes, `bonus` times
+    /// the recipe's outputs
+    pub fn can_run(&self, bonus: u32) -> bool {
+        let has_inputs = self
+            .recipe
+            .inputs
+            .iter()
+            .all(|(good, amount)| Production::buffered(&self.input, *good) >= *amount);
+        let has_room = self.recipe.outputs.iter().all(|(good, amount)| {
+            Production::buffered(&self.output, *good) + amount * bonus <= amount * BUFFER_BATCHES
+        });
+
+        has_inputs && has_room
+    }
+
+    /// Adds or takes away `Production` when buildings are placed or upgraded
+    pub(super) fn track_buildings(
+        mut commands: Commands,
+        building_query: Query<(Entity, &BuildingType, Option<&Production>), Changed<BuildingType>>,
+    ) {
+        for (entity, building, production) in building_query.iter() {
+            match (building.recipe(), production) {
+                (Some(recipe), None) => {
+                    commands.entity(entity).insert(Production::new(recipe));
+                }
+                (None, Some(_)) => {
+                    commands.entity(entity).remove::<Production>();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Lets go of workers whose building is gone or has lost its road, and
+    /// sends workers without a workplace to the entrance of finished
+    /// buildings with a road that are short of staff, if they can walk there
+    #[allow(clippy::type_complexity)]
+    pub(super) fn hire_workers(
+        production_query: Query<
+            (Entity, &Production, &BuildingType, &TilePosition, &Facing),
+            (With<RoadAccess>, Without<Blueprint>),
+        >,
+        mut worker_query: Query<(&mut Worker, &mut Walker, &TilePosition)>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        let mut staff = HashMap::<Entity, usize>::new();
+
+        for (mut worker, _, _) in worker_query.iter_mut() {
+            let Some(workplace) = worker.workplace else {
+                continue;
+            };
+
+            match production_query.contains(workplace) {
+                true => *staff.entry(workplace).or_default() += 1,
+                false => worker.workplace = None,
+            }
+        }
+
+        for (entity, production, building, tile, facing) in production_query.iter() {
+            let mut hired = staff.get(&entity).copied().unwrap_or(0);
+            let entrance = building.entrance(tile.0, *facing);
+
+            for (mut worker, mut walker, worker_tile) in worker_query.iter_mut() {
+                if hired >= production.recipe.workers {
+                    break;
+                }
+                if worker.workplace.is_some() {
+                    continue;
+                }
+
+                let Some(path) = Walker::find_path(&map, &network, worker_tile.0, entrance) else {
+                    continue;
+                };
+
+                worker.workplace = Some(entity);
+                walker.path = path;
+                hired += 1;
+            }
+        }
+    }
+
+    /// Runs batches in finished buildings with a road to the town centre once
+    /// all of their workers have arrived. Farms bring in twice as much
+    /// during a good harvest.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn work(
+        time: Res<Time>,
+        director: Res<EventDirector>,
+        mut production_query: Query<
+            (Entity, &BuildingType, &mut Production),
+            (With<RoadAccess>, Without<Blueprint>),
+        >,
+        worker_query: Query<(&Worker, &Walker)>,
+        mut finished: EventWriter<BatchFinished>,
+    ) {
+        let mut present = HashMap::<Entity, usize>::new();
+
+        for (worker, walker) in worker_query.iter() {
+            if let (Some(workplace), true) = (worker.workplace, walker.is_idle()) {
+                *present.entry(workplace).or_default() += 1;
+            }
+        }
+
+        for (entity, building, mut production) in production_query.iter_mut() {
+            let staffed = present.get(&entity).copied().unwrap_or(0) >= production.recipe.workers;
+            if production.staffed != staffed {
+                production.staffed = staffed;
+            }
+            let bonus = match building {
+                BuildingType::Farm if director.is_active(RandomEvent::GoodHarvest) => 2,
+                _ => 1,
+            };
+            if !staffed || !production.can_run(bonus) {
+                continue;
+            }
+
+            if !production.cycle.tick(time.delta()).just_finished() {
+                continue;
+            }
+
+            let Recipe {
+                inputs, outputs, ..
+            } = production.recipe;
+            for (good, amount) in inputs {
+                *production.input.entry(*good).or_default() -= amount;
+            }
+            for (good, amount) in outputs {
+                *production.output.entry(*good).or_default() += amount * bonus;
+            }
+            finished.send(BatchFinished {
+                entity,
+                building: *building,
+            });
+        }
+    }
+
+    /// Every few seconds carts take what the buildings made to the stockpile
+    /// and top their inputs back up from it, but only along roads and not to
+    /// buildings still under construction
+    #[allow(clippy::type_complexity)]
+    pub(super) fn haul(
+        time: Res<Time>,
+        mut since_last_cart: Local<f32>,
+        mut stockpile: ResMut<Stockpile>,
+        mut production_query: Query<&mut Production, (With<RoadAccess>, Without<Blueprint>)>,
+    ) {
+        *since_last_cart += time.delta_seconds();
+        if *since_last_cart < HAUL_SECONDS {
+            return;
+        }
+        *since_last_cart = 0.0;
+
+        for mut production in production_query.iter_mut() {
+            let made = production
+                .output
+                .drain()
+                .filter(|(_, amount)| *amount > 0)
+                .collect::<Vec<_>>();
+            if !made.is_empty() {
+                stockpile.add(&made);
+            }
+
+            for (good, amount) in production.recipe.inputs {
+                let missing = (amount * BUFFER_BATCHES)
+                    .saturating_sub(Production::buffered(&production.input, *good));
+                if missing == 0 || stockpile.amount(*good) == 0 {
+                    continue;
+                }
+
+                let taken = stockpile.take(*good, missing);
+                *production.input.entry(*good).or_default() += taken;
+            }
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use std::str::FromStr;
+
 use bevy::prelude::*;
+use rand::prelude::random;
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct GlobalState {
     pub world_rows: usize,
     pub world_cols: usize,
     pub block_size: usize,
+    pub seed: u32,
+    pub resource_abundance: ResourceAbundance,
+    pub water_level: WaterLevel,
+    pub world_mode: WorldMode,
 }
 
 impl Default for GlobalState {
@@ -13,6 +20,213 @@ impl Default for GlobalState {
             world_rows: 32,
             world_cols: 64,
             block_size: 64,
+            seed: random::<u32>(),
+            resource_abundance: ResourceAbundance::Normal,
+            water_level: WaterLevel::Normal,
+            world_mode: WorldMode::Fixed,
+        }
+    }
+}
+
+impl GlobalState {
+    /// Half the width and height of the world in pixels, `None` when the world is endless
+    pub fn world_half_extents(&self) -> Option<Vec2> {
+        match self.world_mode {
+            WorldMode::Fixed => Some(Vec2::new(
+                (self.world_cols * self.block_size) as f32 / 2.,
+                (self.world_rows * self.block_size) as f32 / 2.,
+            )),
+            WorldMode::Streaming => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapSize {
+    Small,
+    Medium,
+    Large,
+    Huge,
+    /// Any other columns and rows, picked on the command line
+    Custom(usize, usize),
+}
+
+impl MapSize {
+    /// The preset matching `cols` by `rows`, otherwise a custom size
+    pub fn from_dimensions(cols: usize, rows: usize) -> Self {
+        [
+            MapSize::Small,
+            MapSize::Medium,
+            MapSize::Large,
+            MapSize::Huge,
+        ]
+        .into_iter()
+        .find(|size| size.dimensions() == (cols, rows))
+        .unwrap_or(MapSize::Custom(cols, rows))
+    }
+
+    /// Columns and rows of the world
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            MapSize::Small => (32, 16),
+            MapSize::Medium => (64, 32),
+            MapSize::Large => (128, 64),
+            MapSize::Huge => (512, 512),
+            MapSize::Custom(cols, rows) => (*cols, *rows),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MapSize::Small => "Small",
+            MapSize::Medium => "Medium",
+            MapSize::Large => "Large",
+            MapSize::Huge => "Huge",
+            MapSize::Custom(_, _) => "Custom",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            MapSize::Small => MapSize::Medium,
+            MapSize::Medium => MapSize::Large,
+            MapSize::Large => MapSize::Huge,
+            MapSize::Huge => MapSize::Small,
+            MapSize::Custom(_, _) => MapSize::Small,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResourceAbundance {
+    Scarce,
+    Normal,
+    Abundant,
+}
+
+impl ResourceAbundance {
+    /// Scales how often grass tiles roll a resource
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            ResourceAbundance::Scarce => 0.5,
+            ResourceAbundance::Normal => 1.0,
+            ResourceAbundance::Abundant => 1.5,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResourceAbundance::Scarce => "Scarce",
+            ResourceAbundance::Normal => "Normal",
+            ResourceAbundance::Abundant => "Abundant",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ResourceAbundance::Scarce => ResourceAbundance::Normal,
+            ResourceAbundance::Normal => ResourceAbundance::Abundant,
+            ResourceAbundance::Abundant => ResourceAbundance::Scarce,
+        }
+    }
+}
+
+impl FromStr for ResourceAbundance {
+    type Err = String;
+
+    /// Parses a label, ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [
+            ResourceAbundance::Scarce,
+            ResourceAbundance::Normal,
+            ResourceAbundance::Abundant,
+        ]
+        .into_iter()
+        .find(|abundance| abundance.label().eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("unknown resource amount {}", name))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WaterLevel {
+    Low,
+    Normal,
+    High,
+}
+
+impl WaterLevel {
+    /// Shifts a perlin elevation so more (or less) of the map sits in the water band
+    pub fn adjust(&self, elevation: f32) -> f32 {
+        let offset = match self {
+            WaterLevel::Low => -0.02,
+            WaterLevel::Normal => 0.0,
+            WaterLevel::High => 0.05,
+        };
+
+        (elevation.abs() - offset).max(0.0)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WaterLevel::Low => "Low",
+            WaterLevel::Normal => "Normal",
+            WaterLevel::High => "High",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            WaterLevel::Low => WaterLevel::Normal,
+            WaterLevel::Normal => WaterLevel::High,
+            WaterLevel::High => WaterLevel::Low,
+        }
+    }
+}
+
+impl FromStr for WaterLevel {
+    type Err = String;
+
+    /// Parses a label, ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [WaterLevel::Low, WaterLevel::Normal, WaterLevel::High]
+            .into_iter()
+            .find(|level| level.label().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown water level {}", name))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WorldMode {
+    /// A `world_cols` by `world_rows` map generated up front
+    Fixed,
+    /// An endless map generated chunk by chunk as the camera explores
+    Streaming,
+}
+
+impl WorldMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WorldMode::Fixed => "Fixed",
+            WorldMode::Streaming => "Endless",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            WorldMode::Fixed => WorldMode::Streaming,
+            WorldMode::Streaming => WorldMode::Fixed,
         }
     }
 }
+
+impl FromStr for WorldMode {
+    type Err = String;
+
+    /// Parses a label, ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [WorldMode::Fixed, WorldMode::Streaming]
+            .into_iter()
+            .find(|mode| mode.label().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown world mode {}", name))
+    }
+}
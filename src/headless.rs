@@ -0,0 +1,128 @@
+//! Runs the simulation without a window, renderer or asset files, so tests can
+//! step the game frame by frame and assert on the ECS state.
+
+use bevy::{
+    prelude::*,
+    time::TimeUpdateStrategy,
+    utils::{Duration, Instant},
+};
+
+use crate::{
+    building::{
+        BuildingType, DemolishBuilding, Facing, HistoryStep, PlaceBuilding, RepairBuilding,
+        UpgradeBuilding,
+    },
+    global_state::GlobalState,
+    loading::{BuildingAssets, PersonAssets, TextureAssets},
+    research::{StartResearch, Tech},
+    GameState, SimulationPlugin,
+};
+
+/// Game time between frames in `run_seconds`
+const FRAME_SECONDS: f32 = 0.1;
+
+pub struct HeadlessRunner {
+    pub app: App,
+}
+
+impl HeadlessRunner {
+    /// An app with `MinimalPlugins`, stubbed assets and the simulation, starting
+    /// straight in `GameState::Playing`. The world is generated on the first frame.
+    pub fn new(global_state: GlobalState) -> Self {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(global_state)
+            .insert_resource(TextureAssets::default())
+            .insert_resource(BuildingAssets::default())
+            .insert_resource(PersonAssets::default())
+            .add_state(GameState::Playing)
+            .add_plugin(SimulationPlugin);
+
+        Self { app }
+    }
+
+    pub fn run_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Runs frames a tenth of a second of game time apart until `seconds` have
+    /// passed, without waiting for them in real time
+    pub fn run_seconds(&mut self, seconds: f32) {
+        let frames = (seconds / FRAME_SECONDS).ceil() as usize;
+
+        for _ in 0..frames {
+            let last_update = self
+                .app
+                .world
+                .resource::<Time>()
+                .last_update()
+                .unwrap_or_else(Instant::now);
+            self.app
+                .world
+                .insert_resource(TimeUpdateStrategy::ManualInstant(
+                    last_update + Duration::from_secs_f32(FRAME_SECONDS),
+                ));
+            self.app.update();
+        }
+
+        self.app
+            .world
+            .insert_resource(TimeUpdateStrategy::Automatic);
+    }
+
+    /// Places `building` the same way a click in build mode does
+    pub fn place_building(&mut self, building: BuildingType, tile: IVec2) {
+        self.place_building_facing(building, tile, Facing::North);
+    }
+
+    /// Places `building` turned to `facing`, like pressing R before clicking
+    pub fn place_building_facing(&mut self, building: BuildingType, tile: IVec2, facing: Facing) {
+        self.app.world.send_event(PlaceBuilding {
+            building,
+            tile,
+            facing,
+            free: false,
+            record: true,
+        });
+    }
+
+    /// Tears down the building on `tile` the same way the Delete key does
+    pub fn demolish_building(&mut self, tile: IVec2) {
+        self.app.world.send_event(DemolishBuilding {
+            tile,
+            refund: false,
+            record: true,
+        });
+    }
+
+    /// Upgrades the building on `tile` the same way the U key does
+    pub fn upgrade_building(&mut self, tile: IVec2) {
+        self.app
+            .world
+            .send_event(UpgradeBuilding { tile, record: true });
+    }
+
+    pub fn repair_building(&mut self, tile: IVec2) {
+        self.app.world.send_event(RepairBuilding { tile });
+    }
+
+    pub fn start_research(&mut self, tech: Tech) {
+        self.app.world.send_event(StartResearch { tech });
+    }
+
+    pub fn step_history(&mut self, step: HistoryStep) {
+        self.app.world.send_event(step);
+    }
+
+    /// Whether the game has been won or lost and the simulation has stopped
+    pub fn is_game_over(&self) -> bool {
+        *self.app.world.resource::<State<GameState>>().current() == GameState::GameOver
+    }
+
+    pub fn world(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+}
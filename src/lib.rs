@@ -1,27 +1,47 @@
 mod actions;
 mod audio;
 mod bounds;
-mod building;
-mod global_state;
+pub mod building;
+pub mod cli;
+pub mod director;
+pub mod economy;
+pub mod global_state;
+pub mod headless;
 mod loading;
+pub mod map_export;
 mod menu;
+pub mod minimap;
 mod mouse_position;
+pub mod notifications;
+pub mod objectives;
 mod player;
-mod tiles;
+pub mod research;
+pub mod save;
+pub mod tiles;
 mod ui;
-mod world_gen;
+pub mod units;
+pub mod world_gen;
 
 use crate::audio::InternalAudioPlugin;
+use crate::cli::LaunchOptions;
+use crate::director::DirectorPlugin;
+use crate::economy::EconomyPlugin;
 use crate::global_state::GlobalState;
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
-use crate::world_gen::WorldGenPlugin;
+use crate::minimap::MinimapPlugin;
+use crate::notifications::{NotificationPanelPlugin, NotificationsPlugin};
+use crate::objectives::{ObjectivesPanelPlugin, ObjectivesPlugin};
+use crate::research::{ResearchPanelPlugin, ResearchPlugin};
+use crate::save::SavePlugin;
+use crate::units::{UnitSelectionPlugin, UnitsPlugin};
+use crate::world_gen::{TerrainRenderPlugin, WorldGenPlugin};
 
 use bevy::app::App;
 #[cfg(debug_assertions)]
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
-use building::BuildingPlugin;
+use building::{BuildingIndicatorPlugin, BuildingPlugin};
 use mouse_position::MousePositionPlugin;
 
 // This example game uses States to separate logic
@@ -35,21 +55,31 @@ enum GameState {
     Playing,
     // Here the menu is drawn and waiting for player interaction
     Menu,
+    // The game was won or lost, the end screen is shown and the simulation stops
+    GameOver,
 }
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(GlobalState::default())
+        app.init_resource::<GlobalState>()
+            .init_resource::<LaunchOptions>()
             .add_state(GameState::Loading)
             .add_plugin(LoadingPlugin)
             .add_plugin(MenuPlugin)
             .add_plugin(MousePositionPlugin)
             .add_plugin(InternalAudioPlugin)
-            .add_plugin(WorldGenPlugin)
+            .add_plugin(SimulationPlugin)
+            .add_plugin(TerrainRenderPlugin)
             .add_plugin(ui::GuiPlugin)
-            .add_plugin(BuildingPlugin);
+            .add_plugin(BuildingIndicatorPlugin)
+            .add_plugin(UnitSelectionPlugin)
+            .add_plugin(NotificationPanelPlugin)
+            .add_plugin(MinimapPlugin)
+            .add_plugin(ObjectivesPanelPlugin)
+            .add_plugin(ResearchPanelPlugin)
+            .add_plugin(SavePlugin);
 
         #[cfg(debug_assertions)]
         {
@@ -58,3 +88,20 @@ impl Plugin for GamePlugin {
         }
     }
 }
+
+/// The gameplay that runs without a window or renderer, shared by `GamePlugin`
+/// and the headless runner
+struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(NotificationsPlugin)
+            .add_plugin(WorldGenPlugin)
+            .add_plugin(EconomyPlugin)
+            .add_plugin(ResearchPlugin)
+            .add_plugin(BuildingPlugin)
+            .add_plugin(UnitsPlugin)
+            .add_plugin(DirectorPlugin)
+            .add_plugin(ObjectivesPlugin);
+    }
+}
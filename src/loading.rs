@@ -24,20 +24,21 @@ impl Plugin for LoadingPlugin {
 
 // the following asset collections will be loaded during the State `GameState::Loading`
 // when done loading, they will be inserted as resources (see <https://github.com/NiklasEi/bevy_asset_loader>)
+// `Default` gives collections of empty handles, standing in for the real assets when running headless
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct FontAssets {
     #[asset(path = "fonts/FiraSans-Bold.ttf")]
     pub fira_sans: Handle<Font>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct AudioAssets {
     #[asset(path = "audio/flying.ogg")]
     pub flying: Handle<AudioSource>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct TextureAssets {
     #[asset(path = "textures/bevy.png")]
     pub texture_bevy: Handle<Image>,
@@ -79,7 +80,7 @@ pub struct TextureAssets {
     pub texture_selector_err: Handle<Image>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct PersonAssets {
     #[asset(path = "workers/person.png")]
     pub person: Handle<Image>,
@@ -99,7 +100,7 @@ pub struct PersonAssets {
     pub person_7: Handle<Image>,
 }
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct BuildingAssets {
     #[asset(path = "buildings/barracks.png")]
     pub barracks: Handle<Image>,
@@ -117,4 +118,21 @@ pub struct BuildingAssets {
     pub stall: Handle<Image>,
     #[asset(path = "buildings/town_centre.png")]
     pub town_centre: Handle<Image>,
+    #[asset(path = "buildings/lumber_mill.png")]
+    pub lumber_mill: Handle<Image>,
+    #[asset(path = "buildings/stonecutter.png")]
+    pub stonecutter: Handle<Image>,
+
+    #[asset(path = "roads/single.png")]
+    pub road_single: Handle<Image>,
+    #[asset(path = "roads/end.png")]
+    pub road_end: Handle<Image>,
+    #[asset(path = "roads/straight.png")]
+    pub road_straight: Handle<Image>,
+    #[asset(path = "roads/corner.png")]
+    pub road_corner: Handle<Image>,
+    #[asset(path = "roads/junction.png")]
+    pub road_junction: Handle<Image>,
+    #[asset(path = "roads/cross.png")]
+    pub road_cross: Handle<Image>,
 }
@@ -7,12 +7,22 @@ use bevy::winit::WinitWindows;
 use bevy::DefaultPlugins;
 // use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_pancam::PanCamPlugin;
+use game_growth::cli::{LaunchOptions, USAGE};
 use game_growth::GamePlugin;
 use std::io::Cursor;
+use std::{env, process};
 use winit::window::Icon;
 
 fn main() {
+    let (global_state, launch_options) = LaunchOptions::from_args(env::args().skip(1))
+        .unwrap_or_else(|error| {
+            eprintln!("{}\n\n{}", error, USAGE);
+            process::exit(1);
+        });
+
     App::new()
+        .insert_resource(global_state)
+        .insert_resource(launch_options)
         .insert_resource(Msaa { samples: 1 })
         .insert_resource(ClearColor(Color::BLACK))
         .add_plugins(DefaultPlugins.set(WindowPlugin {
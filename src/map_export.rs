@@ -0,0 +1,71 @@
+//! Draws a generated map as an image or as text, so seeds can be compared
+//! without starting the game.
+
+use bevy::prelude::*;
+use image::{Rgba, RgbaImage};
+
+use crate::{tiles::*, world_gen::WorldMap};
+
+const START_COLOUR: Color = Color::rgb(0.86, 0.16, 0.16);
+
+/// Draws every tile as a `scale` by `scale` square, with north at the top of the image
+pub fn render_png(map: &WorldMap, scale: u32) -> RgbaImage {
+    let scale = scale.max(1);
+    let width = map.cols as u32 * scale;
+    let height = map.rows as u32 * scale;
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let tile = IVec2::new((x / scale) as i32, map.rows as i32 - 1 - (y / scale) as i32);
+
+        let colour = match map.get(tile) {
+            Some(_) if tile == map.start => START_COLOUR,
+            Some(map_tile) => map_tile.map_colour(),
+            None => Color::BLACK,
+        };
+
+        Rgba(
+            colour
+                .as_rgba_f32()
+                .map(|channel| (channel * 255.).round() as u8),
+        )
+    })
+}
+
+/// One character per tile, one line per row, with north on the first line.
+///
+/// `~` water, `.` sand, `:` dirt, `,` grass, `^` stone, `T` tree, `o` stone
+/// resource, `b` berries and `S` the start location.
+pub fn render_ascii(map: &WorldMap) -> String {
+    let mut ascii = String::with_capacity((map.cols + 1) * map.rows);
+
+    for y in (0..map.rows as i32).rev() {
+        for x in 0..map.cols as i32 {
+            let tile = IVec2::new(x, y);
+
+            let character = match map.get(tile) {
+                Some(_) if tile == map.start => 'S',
+                Some(map_tile) => ascii_character(map_tile.tile_type, map_tile.resource),
+                None => ' ',
+            };
+
+            ascii.push(character);
+        }
+
+        ascii.push('\n');
+    }
+
+    ascii
+}
+
+fn ascii_character(tile_type: TileType, resource: Option<ResourceKind>) -> char {
+    match (resource, tile_type) {
+        (Some(ResourceKind::Tree), _) => 'T',
+        (Some(ResourceKind::Stone), _) => 'o',
+        (Some(ResourceKind::Berry), _) => 'b',
+        (None, TileType::Water) => '~',
+        (None, TileType::Sand) => '.',
+        (None, TileType::Dirt) => ':',
+        (None, TileType::Grass) => ',',
+        (None, TileType::Stone) => '^',
+    }
+}
@@ -1,19 +1,33 @@
-use crate::global_state::GlobalState;
+use crate::cli::LaunchOptions;
+use crate::global_state::{GlobalState, MapSize, ResourceAbundance, WaterLevel, WorldMode};
 use crate::loading::FontAssets;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_pancam::PanCam;
+use rand::prelude::random;
 
 pub struct MenuPlugin;
 
-/// This plugin is responsible for the game menu (containing only one button...)
+/// Digits in `u32::MAX`, the longest seed there is
+const MAX_SEED_DIGITS: usize = 10;
+
+/// This plugin is responsible for the game menu (the new game options and a Play button)
 /// The menu is only drawn during the State `GameState::Menu` and is removed when that state is exited
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ButtonColors>()
+            .init_resource::<NewGameOptions>()
             .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(setup_menu))
-            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(click_play_button))
-            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(cleanup_menu));
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(highlight_buttons)
+                    .with_system(click_option_button)
+                    .with_system(type_seed)
+                    .with_system(update_option_labels)
+                    .with_system(click_play_button),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(cleanup_menu))
+            .add_system(follow_world_bounds);
     }
 }
 
@@ -32,63 +46,203 @@ impl Default for ButtonColors {
     }
 }
 
+/// The options picked on the new game screen, applied to `GlobalState` on Play
+#[derive(Resource)]
+struct NewGameOptions {
+    map_size: MapSize,
+    /// Typed digits, a random seed is used when empty
+    seed: String,
+    resource_abundance: ResourceAbundance,
+    water_level: WaterLevel,
+    world_mode: WorldMode,
+}
+
+/// Starts from the `GlobalState`, so options given on the command line show up in the menu
+impl FromWorld for NewGameOptions {
+    fn from_world(world: &mut World) -> Self {
+        let global_state = world.resource::<GlobalState>();
+        let seed = world
+            .get_resource::<LaunchOptions>()
+            .and_then(|options| options.seed)
+            .map_or(String::new(), |seed| seed.to_string());
+
+        Self {
+            map_size: MapSize::from_dimensions(global_state.world_cols, global_state.world_rows),
+            seed,
+            resource_abundance: global_state.resource_abundance,
+            water_level: global_state.water_level,
+            world_mode: global_state.world_mode,
+        }
+    }
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum MenuButton {
+    WorldMode,
+    MapSize,
+    Seed,
+    Resources,
+    Water,
+    Play,
+}
+
+impl MenuButton {
+    fn label(&self, options: &NewGameOptions) -> String {
+        match self {
+            MenuButton::WorldMode => format!("World: {}", options.world_mode.label()),
+            MenuButton::MapSize => {
+                let (cols, rows) = options.map_size.dimensions();
+                format!("Map: {} ({}x{})", options.map_size.label(), cols, rows)
+            }
+            MenuButton::Seed => match options.seed.is_empty() {
+                true => "Seed: random".to_string(),
+                false => format!("Seed: {}", options.seed),
+            },
+            MenuButton::Resources => {
+                format!("Resources: {}", options.resource_abundance.label())
+            }
+            MenuButton::Water => format!("Water: {}", options.water_level.label()),
+            MenuButton::Play => "Play".to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ButtonLabel(MenuButton);
+
 fn setup_menu(
     mut commands: Commands,
     font_assets: Res<FontAssets>,
     button_colors: Res<ButtonColors>,
     global_state: Res<GlobalState>,
+    options: Res<NewGameOptions>,
+    launch_options: Res<LaunchOptions>,
+    mut state: ResMut<State<GameState>>,
 ) {
-    let offset = global_state.block_size * 0;
-    let max_vertical_offset = global_state.block_size * 0;
-    let max_vertical = ((global_state.world_rows * global_state.block_size) / 2) + offset;
-    let max_horizontal = ((global_state.world_cols * global_state.block_size) / 2) + offset;
-
-    commands.spawn((
-        Camera2dBundle::default(),
-        PanCam {
-            grab_buttons: vec![MouseButton::Middle],
-            min_scale: 1.,
-            min_x: Some(-(max_horizontal as f32)),
-            max_x: Some(max_horizontal as f32),
-            min_y: Some(-(max_vertical as f32 + max_vertical_offset as f32)),
-            max_y: Some(max_vertical as f32),
-            ..default()
-        },
-    ));
+    let mut pancam = PanCam {
+        grab_buttons: vec![MouseButton::Middle],
+        min_scale: 1.,
+        ..default()
+    };
+    set_world_bounds(&mut pancam, &global_state);
+
+    commands.spawn((Camera2dBundle::default(), pancam));
+
+    if launch_options.skip_menu {
+        state.set(GameState::Playing).unwrap();
+        return;
+    }
 
     commands
-        .spawn(ButtonBundle {
-            style: Style {
-                size: Size::new(Val::Px(200.0), Val::Px(75.0)),
-                margin: UiRect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
+        .spawn((
+            MenuRoot,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::ColumnReverse,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
                 ..Default::default()
             },
-            background_color: button_colors.normal.into(),
-            ..Default::default()
-        })
+        ))
         .with_children(|parent| {
             parent.spawn(TextBundle {
-                text: Text {
-                    sections: vec![TextSection {
-                        value: "Play".to_string(),
-                        style: TextStyle {
+                text: Text::from_section(
+                    "New Game",
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 48.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                style: Style {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+
+            for button in [
+                MenuButton::WorldMode,
+                MenuButton::MapSize,
+                MenuButton::Seed,
+                MenuButton::Resources,
+                MenuButton::Water,
+            ] {
+                spawn_button(parent, button, &options, &font_assets, &button_colors, 24.0);
+            }
+
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    "Type digits to set the seed, click it to randomise",
+                    TextStyle {
+                        font: font_assets.fira_sans.clone(),
+                        font_size: 14.0,
+                        color: Color::hex("999999").unwrap(),
+                    },
+                ),
+                ..Default::default()
+            });
+
+            spawn_button(
+                parent,
+                MenuButton::Play,
+                &options,
+                &font_assets,
+                &button_colors,
+                40.0,
+            );
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button: MenuButton,
+    options: &NewGameOptions,
+    font_assets: &FontAssets,
+    button_colors: &ButtonColors,
+    font_size: f32,
+) {
+    parent
+        .spawn((
+            button,
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(320.0), Val::Px(font_size + 35.0)),
+                    margin: UiRect::all(Val::Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ButtonLabel(button),
+                TextBundle {
+                    text: Text::from_section(
+                        button.label(options),
+                        TextStyle {
                             font: font_assets.fira_sans.clone(),
-                            font_size: 40.0,
+                            font_size,
                             color: Color::BLACK,
                         },
-                    }],
-                    alignment: Default::default(),
+                    ),
+                    ..Default::default()
                 },
-                ..Default::default()
-            });
+            ));
         });
 }
 
-fn click_play_button(
+fn highlight_buttons(
     button_colors: Res<ButtonColors>,
-    mut state: ResMut<State<GameState>>,
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<Button>),
@@ -96,19 +250,129 @@ fn click_play_button(
 ) {
     for (interaction, mut color) in &mut interaction_query {
         match *interaction {
-            Interaction::Clicked => {
-                state.set(GameState::Playing).unwrap();
-            }
             Interaction::Hovered => {
                 *color = button_colors.hovered.into();
             }
             Interaction::None => {
                 *color = button_colors.normal.into();
             }
+            Interaction::Clicked => {}
+        }
+    }
+}
+
+fn click_option_button(
+    mut options: ResMut<NewGameOptions>,
+    interaction_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            MenuButton::WorldMode => options.world_mode = options.world_mode.next(),
+            MenuButton::MapSize => options.map_size = options.map_size.next(),
+            MenuButton::Seed => options.seed.clear(),
+            MenuButton::Resources => options.resource_abundance = options.resource_abundance.next(),
+            MenuButton::Water => options.water_level = options.water_level.next(),
+            MenuButton::Play => {}
         }
     }
 }
 
-fn cleanup_menu(mut commands: Commands, button: Query<Entity, With<Button>>) {
-    commands.entity(button.single()).despawn_recursive();
+fn type_seed(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut options: ResMut<NewGameOptions>,
+) {
+    for event in characters.iter() {
+        if !event.char.is_ascii_digit() || options.seed.len() >= MAX_SEED_DIGITS {
+            continue;
+        }
+
+        // Ten digit seeds only go as far as `u32::MAX`
+        let typed = format!("{}{}", options.seed, event.char);
+        if typed.parse::<u32>().is_ok() {
+            options.seed = typed;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        options.seed.pop();
+    }
+}
+
+fn update_option_labels(
+    options: Res<NewGameOptions>,
+    mut label_query: Query<(&mut Text, &ButtonLabel)>,
+) {
+    if !options.is_changed() {
+        return;
+    }
+
+    for (mut text, label) in &mut label_query {
+        text.sections[0].value = label.0.label(&options);
+    }
+}
+
+fn click_play_button(
+    mut state: ResMut<State<GameState>>,
+    mut global_state: ResMut<GlobalState>,
+    options: Res<NewGameOptions>,
+    interaction_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Clicked || *button != MenuButton::Play {
+            continue;
+        }
+
+        let seed = match options.seed.is_empty() {
+            true => random(),
+            false => match options.seed.parse() {
+                Ok(seed) => seed,
+                Err(error) => {
+                    warn!("Seed {} is not a valid seed: {}", options.seed, error);
+                    continue;
+                }
+            },
+        };
+
+        let (cols, rows) = options.map_size.dimensions();
+        global_state.world_cols = cols;
+        global_state.world_rows = rows;
+        global_state.seed = seed;
+        global_state.resource_abundance = options.resource_abundance;
+        global_state.water_level = options.water_level;
+        global_state.world_mode = options.world_mode;
+
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Keeps the `PanCam` bounds in line with the size of the world
+fn follow_world_bounds(global_state: Res<GlobalState>, mut camera_query: Query<&mut PanCam>) {
+    if !global_state.is_changed() {
+        return;
+    }
+
+    for mut pancam in &mut camera_query {
+        set_world_bounds(&mut pancam, &global_state);
+    }
+}
+
+/// Clamps the camera to the world, an endless world leaves it unbounded
+fn set_world_bounds(pancam: &mut PanCam, global_state: &GlobalState) {
+    let extents = global_state.world_half_extents();
+
+    pancam.min_x = extents.map(|extents| -extents.x);
+    pancam.max_x = extents.map(|extents| extents.x);
+    pancam.min_y = extents.map(|extents| -extents.y);
+    pancam.max_y = extents.map(|extents| extents.y);
+}
+
+fn cleanup_menu(mut commands: Commands, root: Query<Entity, With<MenuRoot>>) {
+    for entity in &root {
+        commands.entity(entity).despawn_recursive();
+    }
 }
@@ -0,0 +1,450 @@
+//! Minimap in the bottom left corner. Every tile is drawn in its map colour
+//! with buildings and units on top, the part of the world the camera sees is
+//! outlined, and clicking or dragging on it moves the camera there. Only the
+//! tiles that changed are painted again.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use crate::{
+    building::{Building, BuildingType, Facing},
+    global_state::WorldMode,
+    mouse_position::MousePosition,
+    tiles::TilePosition,
+    units::{Raider, Soldier, Worker},
+    world_gen::WorldMap,
+    GameState,
+};
+
+/// Width of the minimap on screen, in pixels
+pub const MINIMAP_WIDTH: f32 = 192.0;
+/// Tiles shown around the camera in a world without edges
+const STREAMING_VIEW: UVec2 = UVec2::new(96, 48);
+/// Seconds between the changes to the minimap being drawn
+const REDRAW_SECONDS: f32 = 0.25;
+/// Thickness of the viewport outline, in pixels
+const OUTLINE: f32 = 1.0;
+const BUILDING_COLOUR: Color = Color::rgb(0.55, 0.35, 0.2);
+const ROAD_COLOUR: Color = Color::rgb(0.75, 0.7, 0.6);
+const WORKER_COLOUR: Color = Color::WHITE;
+const SOLDIER_COLOUR: Color = Color::rgb(1.0, 0.55, 0.55);
+const RAIDER_COLOUR: Color = Color::rgb(0.1, 0.05, 0.05);
+
+/// The tiles the minimap covers, from `origin` in the bottom left corner
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MinimapView {
+    pub origin: IVec2,
+    pub size: UVec2,
+}
+
+impl MinimapView {
+    /// The whole map, or the tiles around `centre` when the world has no edges
+    pub fn new(map: &WorldMap, centre: IVec2) -> Self {
+        match map.mode {
+            WorldMode::Fixed => Self {
+                origin: IVec2::ZERO,
+                size: UVec2::new(map.cols as u32, map.rows as u32).max(UVec2::ONE),
+            },
+            WorldMode::Streaming => Self {
+                origin: centre - (STREAMING_VIEW / 2).as_ivec2(),
+                size: STREAMING_VIEW,
+            },
+        }
+    }
+
+    /// The tile under `point`, measured from the top left corner of the
+    /// minimap with its bottom right corner at `(1, 1)`
+    pub fn tile_at(&self, point: Vec2) -> IVec2 {
+        let point = point.clamp(Vec2::ZERO, Vec2::ONE);
+        let offset = Vec2::new(point.x, 1.0 - point.y) * self.size.as_vec2();
+
+        self.origin
+            + offset
+                .floor()
+                .as_ivec2()
+                .min(self.size.as_ivec2() - IVec2::ONE)
+    }
+
+    /// Where a position given in tiles, which can fall between tiles, sits on
+    /// the minimap, measured the same way as `tile_at`
+    pub fn point_of(&self, tile: Vec2) -> Vec2 {
+        let offset = (tile - self.origin.as_vec2() + Vec2::splat(0.5)) / self.size.as_vec2();
+
+        Vec2::new(offset.x, 1.0 - offset.y)
+    }
+
+    /// Index of the first byte of `tile`'s pixel, images start at the top row
+    fn pixel(&self, tile: IVec2) -> Option<usize> {
+        let offset = tile - self.origin;
+        if offset.cmplt(IVec2::ZERO).any() || offset.cmpge(self.size.as_ivec2()).any() {
+            return None;
+        }
+
+        let row = self.size.y as i32 - 1 - offset.y;
+        Some((row as usize * self.size.x as usize + offset.x as usize) * 4)
+    }
+
+    /// Colours `tile`'s pixel in `image`, tiles outside of the view are skipped
+    fn paint(&self, image: &mut Image, tile: IVec2, colour: Color) {
+        if let Some(pixel) = self.pixel(tile) {
+            let rgba = colour
+                .as_rgba_f32()
+                .map(|channel| (channel * 255.).round() as u8);
+            image.data[pixel..pixel + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    /// Height of the minimap on screen for its width
+    fn height(&self) -> f32 {
+        MINIMAP_WIDTH * self.size.y as f32 / self.size.x as f32
+    }
+}
+
+#[derive(Component)]
+struct Minimap {
+    image: Handle<Image>,
+    view: Option<MinimapView>,
+    /// Colour of every tile a building stands on
+    buildings: HashMap<IVec2, Color>,
+    /// Tiles each building stands on
+    footprints: HashMap<Entity, Vec<IVec2>>,
+    /// Tiles to paint again on the next redraw
+    stale: HashSet<IVec2>,
+    /// Tiles units were drawn on in the last redraw
+    units: Vec<IVec2>,
+}
+
+impl Minimap {
+    fn add_building(
+        &mut self,
+        entity: Entity,
+        building: &BuildingType,
+        anchor: IVec2,
+        facing: Facing,
+    ) {
+        let colour = match building {
+            BuildingType::Road => ROAD_COLOUR,
+            _ => BUILDING_COLOUR,
+        };
+        let tiles = building.tiles(anchor, facing);
+
+        for tile in tiles.iter() {
+            self.buildings.insert(*tile, colour);
+        }
+        self.stale.extend(tiles.iter().copied());
+        self.footprints.insert(entity, tiles);
+    }
+
+    fn remove_building(&mut self, entity: Entity) {
+        let Some(tiles) = self.footprints.remove(&entity) else {
+            return;
+        };
+
+        for tile in tiles.iter() {
+            self.buildings.remove(tile);
+        }
+        self.stale.extend(tiles);
+    }
+}
+
+/// Outline of what the camera sees
+#[derive(Component)]
+struct ViewportOutline;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Playing).with_system(MinimapPlugin::spawn),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(MinimapPlugin::track_buildings)
+                .with_system(MinimapPlugin::redraw.after(MinimapPlugin::track_buildings))
+                .with_system(MinimapPlugin::show_viewport.after(MinimapPlugin::redraw))
+                .with_system(MinimapPlugin::pan_on_click.after(MinimapPlugin::redraw)),
+        );
+    }
+}
+
+impl MinimapPlugin {
+    fn spawn(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        image.sampler_descriptor = ImageSampler::nearest();
+        let image = images.add(image);
+
+        commands
+            .spawn((
+                Minimap {
+                    image: image.clone(),
+                    view: None,
+                    buildings: HashMap::new(),
+                    footprints: HashMap::new(),
+                    stale: HashSet::new(),
+                    units: Vec::new(),
+                },
+                // A button so that it knows when it is clicked
+                ButtonBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect::new(
+                            Val::Px(10.),
+                            Val::Undefined,
+                            Val::Undefined,
+                            Val::Px(10.),
+                        ),
+                        size: Size::new(Val::Px(MINIMAP_WIDTH), Val::Px(MINIMAP_WIDTH / 2.)),
+                        overflow: Overflow::Hidden,
+                        ..default()
+                    },
+                    image: image.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn((
+                        ViewportOutline,
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        // One line along each edge
+                        for position in [
+                            UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(0.), Val::Undefined),
+                            UiRect::new(Val::Px(0.), Val::Px(0.), Val::Undefined, Val::Px(0.)),
+                            UiRect::new(Val::Px(0.), Val::Undefined, Val::Px(0.), Val::Px(0.)),
+                            UiRect::new(Val::Undefined, Val::Px(0.), Val::Px(0.), Val::Px(0.)),
+                        ] {
+                            let horizontal =
+                                position.bottom == Val::Undefined || position.top == Val::Undefined;
+                            let size = match horizontal {
+                                true => Size::new(Val::Auto, Val::Px(OUTLINE)),
+                                false => Size::new(Val::Px(OUTLINE), Val::Auto),
+                            };
+
+                            parent.spawn(NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position,
+                                    size,
+                                    ..default()
+                                },
+                                background_color: Color::WHITE.into(),
+                                ..default()
+                            });
+                        }
+                    });
+            });
+    }
+
+    /// The tile the middle of the screen looks at
+    fn camera_tile(map: &WorldMap, camera: &Transform) -> IVec2 {
+        map.world_to_tile(camera.translation.truncate())
+    }
+
+    /// Keeps track of the tiles buildings cover, so that only the tiles of
+    /// buildings that were placed, changed or removed are painted again
+    #[allow(clippy::type_complexity)]
+    fn track_buildings(
+        mut minimap_query: Query<&mut Minimap>,
+        changed_query: Query<
+            (Entity, &BuildingType, &TilePosition, &Facing),
+            (
+                With<Building>,
+                Or<(
+                    Changed<BuildingType>,
+                    Changed<TilePosition>,
+                    Changed<Facing>,
+                )>,
+            ),
+        >,
+        removed: RemovedComponents<Building>,
+    ) {
+        for mut minimap in minimap_query.iter_mut() {
+            for entity in removed.iter() {
+                minimap.remove_building(entity);
+            }
+            for (entity, building, tile, facing) in changed_query.iter() {
+                minimap.remove_building(entity);
+                minimap.add_building(entity, building, tile.0, *facing);
+            }
+        }
+    }
+
+    /// Paints what changed a few times a second. The whole minimap is only
+    /// painted again when the part of the world it covers moves, otherwise
+    /// only changed terrain, changed buildings and the units are.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn redraw(
+        time: Res<Time>,
+        mut since_last: Local<f32>,
+        map: Option<ResMut<WorldMap>>,
+        mut images: ResMut<Assets<Image>>,
+        mut minimap_query: Query<(&mut Minimap, &mut Style)>,
+        camera_query: Query<&Transform, With<Camera2d>>,
+        building_query: Query<(Entity, &BuildingType, &TilePosition, &Facing), With<Building>>,
+        unit_query: Query<
+            (&TilePosition, Option<&Soldier>, Option<&Raider>),
+            Or<(With<Worker>, With<Soldier>, With<Raider>)>,
+        >,
+    ) {
+        let Some(mut map) = map else {
+            return;
+        };
+        *since_last += time.delta_seconds();
+        if *since_last < REDRAW_SECONDS {
+            return;
+        }
+        *since_last = 0.0;
+
+        let centre = camera_query
+            .get_single()
+            .map_or(map.start, |camera| MinimapPlugin::camera_tile(&map, camera));
+        let view = MinimapView::new(&map, centre);
+        let dirty_chunks = map.take_minimap_dirty_chunks();
+
+        for (mut minimap, mut style) in minimap_query.iter_mut() {
+            let Some(image) = images.get_mut(&minimap.image) else {
+                continue;
+            };
+
+            // Units are drawn over the other layers, so wherever they were
+            // is painted again before they are drawn where they are now
+            let mut stale = std::mem::take(&mut minimap.stale);
+            stale.extend(minimap.units.drain(..));
+
+            if minimap.view != Some(view) {
+                if minimap.view.map(|old| old.size) != Some(view.size) {
+                    image.resize(Extent3d {
+                        width: view.size.x,
+                        height: view.size.y,
+                        depth_or_array_layers: 1,
+                    });
+                    style.size.height = Val::Px(view.height());
+                }
+                minimap.view = Some(view);
+
+                minimap.buildings.clear();
+                minimap.footprints.clear();
+                for (entity, building, tile, facing) in building_query.iter() {
+                    minimap.add_building(entity, building, tile.0, *facing);
+                }
+
+                stale.clear();
+                for y in 0..view.size.y as i32 {
+                    for x in 0..view.size.x as i32 {
+                        stale.insert(view.origin + IVec2::new(x, y));
+                    }
+                }
+            } else {
+                stale.extend(dirty_chunks.iter().copied().flat_map(WorldMap::chunk_tiles));
+            }
+
+            for tile in stale {
+                let colour = minimap.buildings.get(&tile).copied().unwrap_or_else(|| {
+                    map.get(tile)
+                        .map_or(Color::BLACK, |map_tile| map_tile.map_colour())
+                });
+                view.paint(image, tile, colour);
+            }
+
+            for (tile, soldier, raider) in unit_query.iter() {
+                let colour = match (soldier, raider) {
+                    (_, Some(_)) => RAIDER_COLOUR,
+                    (Some(_), _) => SOLDIER_COLOUR,
+                    _ => WORKER_COLOUR,
+                };
+                view.paint(image, tile.0, colour);
+                minimap.units.push(tile.0);
+            }
+        }
+    }
+
+    /// Outlines the part of the world the camera sees
+    fn show_viewport(
+        map: Option<Res<WorldMap>>,
+        minimap_query: Query<&Minimap>,
+        camera_query: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+        mut outline_query: Query<&mut Style, With<ViewportOutline>>,
+    ) {
+        let (Some(map), Ok((camera, projection))) = (map, camera_query.get_single()) else {
+            return;
+        };
+        let Some(view) = minimap_query.iter().find_map(|minimap| minimap.view) else {
+            return;
+        };
+
+        let half_size = Vec2::new(
+            projection.right - projection.left,
+            projection.top - projection.bottom,
+        ) * projection.scale
+            / 2.0;
+        // Positions in tiles, tile centres on whole numbers
+        let origin = map.tile_to_world(IVec2::ZERO);
+        let to_tiles = |world: Vec2| (world - origin) / map.block_size;
+        let centre = camera.translation.truncate();
+
+        let top_left = view.point_of(to_tiles(centre + Vec2::new(-half_size.x, half_size.y)));
+        let bottom_right = view.point_of(to_tiles(centre + Vec2::new(half_size.x, -half_size.y)));
+        let minimap_size = Vec2::new(MINIMAP_WIDTH, view.height());
+        let min = top_left.clamp(Vec2::ZERO, Vec2::ONE) * minimap_size;
+        let max = bottom_right.clamp(Vec2::ZERO, Vec2::ONE) * minimap_size;
+
+        for mut style in outline_query.iter_mut() {
+            style.position.left = Val::Px(min.x);
+            style.position.top = Val::Px(min.y);
+            style.size = Size::new(Val::Px(max.x - min.x), Val::Px(max.y - min.y));
+        }
+    }
+
+    /// Moves the camera to the spot on the minimap under the mouse while it
+    /// is held down
+    fn pan_on_click(
+        map: Option<Res<WorldMap>>,
+        mouse: Res<MousePosition>,
+        minimap_query: Query<(&Minimap, &Interaction, &Node, &GlobalTransform)>,
+        mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (minimap, interaction, node, transform) in minimap_query.iter() {
+            let (Interaction::Clicked, Some(view)) = (interaction, minimap.view) else {
+                continue;
+            };
+
+            let top_left = transform.translation().truncate() - node.size() / 2.0;
+            let point = (mouse.cursor_ui - top_left) / node.size();
+            let position = map.tile_to_world(view.tile_at(point));
+
+            for mut camera in camera_query.iter_mut() {
+                camera.translation.x = position.x;
+                camera.translation.y = position.y;
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Telling the player what happened. Any plugin can send a `Notification`,
+//! every one of them is kept in the event log and pops up as a toast.
+
+use bevy::prelude::*;
+
+use crate::{director::EventDirector, GameState};
+
+mod panel;
+
+pub use panel::NotificationPanelPlugin;
+
+/// How many notifications the event log keeps before dropping the oldest
+const LOG_LENGTH: usize = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Severity {
+    Info,
+    Good,
+    Warning,
+    Danger,
+}
+
+impl Severity {
+    pub fn colour(&self) -> Color {
+        match self {
+            Severity::Info => Color::rgba(0.2, 0.2, 0.25, 0.9),
+            Severity::Good => Color::rgba(0.15, 0.4, 0.2, 0.9),
+            Severity::Warning => Color::rgba(0.55, 0.4, 0.1, 0.9),
+            Severity::Danger => Color::rgba(0.6, 0.15, 0.15, 0.9),
+        }
+    }
+}
+
+/// Something the player should know about, with the tile it happened on
+/// when it happened somewhere in particular
+#[derive(Clone, PartialEq, Debug)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    pub tile: Option<IVec2>,
+}
+
+impl Notification {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            tile: None,
+        }
+    }
+
+    pub fn at(mut self, tile: IVec2) -> Self {
+        self.tile = Some(tile);
+        self
+    }
+}
+
+/// A notification and the game day it was sent on
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoggedNotification {
+    pub notification: Notification,
+    pub day: u32,
+}
+
+/// Every notification sent so far, oldest first
+#[derive(Resource, Default)]
+pub struct NotificationLog {
+    entries: Vec<LoggedNotification>,
+}
+
+impl NotificationLog {
+    pub fn entries(&self) -> &[LoggedNotification] {
+        &self.entries
+    }
+
+    fn record(
+        mut events: EventReader<Notification>,
+        mut log: ResMut<NotificationLog>,
+        director: Res<EventDirector>,
+    ) {
+        for notification in events.iter() {
+            log.entries.push(LoggedNotification {
+                notification: notification.clone(),
+                day: director.day,
+            });
+        }
+
+        let overflow = log.entries.len().saturating_sub(LOG_LENGTH);
+        if overflow > 0 {
+            log.entries.drain(..overflow);
+        }
+    }
+}
+
+/// Keeps the event log, works without a window. The toasts and the log panel
+/// live in `NotificationPanelPlugin`.
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Notification>()
+            .init_resource::<NotificationLog>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(NotificationLog::record),
+            );
+    }
+}
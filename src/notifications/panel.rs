@@ -0,0 +1,327 @@
+//! Toasts in the top right corner for every new notification, and the event
+//! log next to the minimap, toggled with L and scrolled with the mouse wheel.
+//! Clicking either moves the camera to where it happened.
+
+use bevy::{input::mouse::MouseWheel, prelude::*, ui::FocusPolicy};
+use bevy_pancam::PanCam;
+
+use super::{Notification, NotificationLog};
+use crate::{loading::FontAssets, minimap::MINIMAP_WIDTH, world_gen::WorldMap, GameState};
+
+/// Seconds a toast stays up
+const TOAST_SECONDS: f32 = 6.0;
+/// Toasts shown at once, the oldest goes when another comes in
+const MAX_TOASTS: usize = 5;
+const LOG_WIDTH: f32 = 380.0;
+const LOG_HEIGHT: f32 = 220.0;
+/// Pixels the log scrolls for one line of the mouse wheel
+const SCROLL_LINE: f32 = 20.0;
+
+#[derive(Component)]
+struct ToastList;
+
+#[derive(Component)]
+struct Toast(Timer);
+
+/// Clicking it moves the camera to this tile
+#[derive(Component)]
+struct PanTarget(IVec2);
+
+#[derive(Component)]
+struct EventLogPanel;
+
+/// Entries of the log, moved up and down to scroll through them
+#[derive(Component, Default)]
+struct EventLogList {
+    scroll: f32,
+}
+
+/// The mouse driven side of notifications, the log itself is kept by
+/// `NotificationsPlugin`
+pub struct NotificationPanelPlugin;
+
+impl Plugin for NotificationPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Playing).with_system(NotificationPanelPlugin::spawn),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(NotificationPanelPlugin::show_toasts)
+                .with_system(NotificationPanelPlugin::expire_toasts)
+                .with_system(NotificationPanelPlugin::update_log)
+                .with_system(NotificationPanelPlugin::toggle_log)
+                .with_system(NotificationPanelPlugin::scroll_log)
+                .with_system(NotificationPanelPlugin::pan_to_clicked),
+        );
+    }
+}
+
+impl NotificationPanelPlugin {
+    fn spawn(mut commands: Commands) {
+        commands.spawn((
+            ToastList,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect::new(
+                        Val::Undefined,
+                        Val::Px(10.),
+                        // Below the current objective
+                        Val::Px(44.),
+                        Val::Undefined,
+                    ),
+                    size: Size::new(Val::Px(320.), Val::Auto),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    ..default()
+                },
+                focus_policy: FocusPolicy::Pass,
+                ..default()
+            },
+        ));
+
+        commands
+            .spawn((
+                EventLogPanel,
+                // A button so that it knows when the mouse is over it
+                ButtonBundle {
+                    style: Style {
+                        display: Display::None,
+                        position_type: PositionType::Absolute,
+                        position: UiRect::new(
+                            Val::Px(MINIMAP_WIDTH + 20.),
+                            Val::Undefined,
+                            Val::Undefined,
+                            Val::Px(10.),
+                        ),
+                        size: Size::new(Val::Px(LOG_WIDTH), Val::Px(LOG_HEIGHT)),
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::Hidden,
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    EventLogList::default(),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    },
+                ));
+            });
+    }
+
+    fn entry(
+        fonts: &FontAssets,
+        notification: &Notification,
+        text: String,
+        font_size: f32,
+    ) -> (ButtonBundle, TextBundle) {
+        let button = ButtonBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(6.)),
+                margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(4.), Val::Px(0.)),
+                ..default()
+            },
+            background_color: notification.severity.colour().into(),
+            // Lets the log panel underneath know the mouse is over it
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        };
+        let text = TextBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font: fonts.fira_sans.clone(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                max_size: Size::new(Val::Px(LOG_WIDTH - 20.), Val::Undefined),
+                ..default()
+            },
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        };
+
+        (button, text)
+    }
+
+    fn show_toasts(
+        mut commands: Commands,
+        fonts: Res<FontAssets>,
+        mut events: EventReader<Notification>,
+        list_query: Query<(Entity, Option<&Children>), With<ToastList>>,
+    ) {
+        let Ok((list, children)) = list_query.get_single() else {
+            return;
+        };
+        let mut shown = children.map_or(vec![], |children| children.to_vec());
+
+        for notification in events.iter() {
+            let (button, text) = NotificationPanelPlugin::entry(
+                &fonts,
+                notification,
+                notification.message.clone(),
+                16.,
+            );
+            let mut toast = commands.spawn((
+                Toast(Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)),
+                button,
+            ));
+            toast.with_children(|parent| {
+                parent.spawn(text);
+            });
+            if let Some(tile) = notification.tile {
+                toast.insert(PanTarget(tile));
+            }
+
+            let toast = toast.id();
+            commands.entity(list).add_child(toast);
+            shown.push(toast);
+        }
+
+        let overflow = shown.len().saturating_sub(MAX_TOASTS);
+        for toast in shown.drain(..overflow) {
+            commands.entity(toast).despawn_recursive();
+        }
+    }
+
+    fn expire_toasts(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut toast_query: Query<(Entity, &mut Toast)>,
+    ) {
+        for (entity, mut toast) in toast_query.iter_mut() {
+            if toast.0.tick(time.delta()).finished() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    /// Lists the log again whenever something is added to it, newest first
+    fn update_log(
+        mut commands: Commands,
+        fonts: Res<FontAssets>,
+        log: Res<NotificationLog>,
+        list_query: Query<Entity, With<EventLogList>>,
+    ) {
+        if !log.is_changed() {
+            return;
+        }
+        let Ok(list) = list_query.get_single() else {
+            return;
+        };
+
+        commands.entity(list).despawn_descendants();
+        commands.entity(list).with_children(|parent| {
+            for logged in log.entries().iter().rev() {
+                let text = format!("Day {}  {}", logged.day, logged.notification.message);
+                let (button, text) =
+                    NotificationPanelPlugin::entry(&fonts, &logged.notification, text, 14.);
+
+                let mut entry = parent.spawn(button);
+                entry.with_children(|parent| {
+                    parent.spawn(text);
+                });
+                if let Some(tile) = logged.notification.tile {
+                    entry.insert(PanTarget(tile));
+                }
+            }
+        });
+    }
+
+    fn toggle_log(
+        keys: Res<Input<KeyCode>>,
+        mut panel_query: Query<&mut Style, With<EventLogPanel>>,
+    ) {
+        if !keys.just_pressed(KeyCode::L) {
+            return;
+        }
+
+        for mut style in panel_query.iter_mut() {
+            style.display = match style.display {
+                Display::None => Display::Flex,
+                Display::Flex => Display::None,
+            };
+        }
+    }
+
+    /// Scrolls the log while the mouse is over it, and keeps the camera from
+    /// zooming at the same time
+    #[allow(clippy::type_complexity)]
+    fn scroll_log(
+        mut wheel: EventReader<MouseWheel>,
+        panel_query: Query<
+            (&Interaction, &Node, &Style),
+            (With<EventLogPanel>, Without<EventLogList>),
+        >,
+        mut list_query: Query<(&mut EventLogList, &mut Style, &Node)>,
+        mut camera_query: Query<&mut PanCam>,
+    ) {
+        let Ok((interaction, panel, panel_style)) = panel_query.get_single() else {
+            return;
+        };
+        let hovered = *interaction != Interaction::None && panel_style.display != Display::None;
+
+        for mut pancam in camera_query.iter_mut() {
+            if pancam.enabled == hovered {
+                pancam.enabled = !hovered;
+            }
+        }
+        if !hovered {
+            wheel.clear();
+            return;
+        }
+
+        let scrolled = wheel.iter().map(|wheel| wheel.y * SCROLL_LINE).sum::<f32>();
+        for (mut list, mut style, node) in list_query.iter_mut() {
+            let max_scroll = (node.size().y - panel.size().y).max(0.0);
+            list.scroll = (list.scroll + scrolled).clamp(-max_scroll, 0.0);
+            style.position.top = Val::Px(list.scroll);
+        }
+    }
+
+    /// Moves the camera to the toast or log entry that was clicked, toasts
+    /// are dismissed by clicking them
+    #[allow(clippy::type_complexity)]
+    fn pan_to_clicked(
+        mut commands: Commands,
+        map: Option<Res<WorldMap>>,
+        clicked_query: Query<
+            (Entity, &Interaction, Option<&PanTarget>, Option<&Toast>),
+            Changed<Interaction>,
+        >,
+        mut camera_query: Query<&mut Transform, With<PanCam>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (entity, interaction, target, toast) in clicked_query.iter() {
+            if *interaction != Interaction::Clicked {
+                continue;
+            }
+
+            if let Some(PanTarget(tile)) = target {
+                let position = map.tile_to_world(*tile);
+                for mut transform in camera_query.iter_mut() {
+                    transform.translation.x = position.x;
+                    transform.translation.y = position.y;
+                }
+            }
+            if toast.is_some() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! What the colony works towards once the town centre stands. Objectives are
+//! completed one after the other and finishing the last one wins the game.
+//! Losing the town centre, or everyone starving, loses it.
+
+use bevy::prelude::*;
+
+use crate::{
+    building::{BuildingDestroyed, BuildingType, TownCentre},
+    economy::{Good, Stockpile},
+    notifications::{Notification, Severity},
+    units::{Raids, Soldier, Worker},
+    GameState,
+};
+
+mod panel;
+
+pub use panel::ObjectivesPanelPlugin;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Objective {
+    /// Workers and soldiers living in the colony
+    Population(u32),
+    /// Goods in the stockpile at the same time
+    Store(Good, u32),
+    /// Raids fought off to the last raider
+    SurviveRaids(u32),
+}
+
+impl Objective {
+    /// Every objective, in the order they have to be completed
+    pub const ORDER: [Objective; 3] = [
+        Objective::Store(Good::Wood, 200),
+        Objective::Population(20),
+        Objective::SurviveRaids(10),
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            Objective::Population(target) => format!("Reach {} population", target),
+            Objective::Store(good, target) => {
+                format!("Store {} {}", target, good.label().to_lowercase())
+            }
+            Objective::SurviveRaids(target) => format!("Survive {} raids", target),
+        }
+    }
+
+    pub fn target(&self) -> u32 {
+        match self {
+            Objective::Population(target)
+            | Objective::Store(_, target)
+            | Objective::SurviveRaids(target) => *target,
+        }
+    }
+
+    /// How far the colony has got, never more than the target
+    pub fn progress(&self, population: u32, stockpile: &Stockpile, raids: &Raids) -> u32 {
+        let progress = match self {
+            Objective::Population(_) => population,
+            Objective::Store(good, _) => stockpile.amount(*good),
+            Objective::SurviveRaids(_) => raids.survived,
+        };
+
+        progress.min(self.target())
+    }
+}
+
+/// How the game ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Victory,
+    TownCentreDestroyed,
+    Starved,
+}
+
+impl Outcome {
+    pub fn is_victory(&self) -> bool {
+        *self == Outcome::Victory
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.is_victory() {
+            true => "Victory",
+            false => "Defeat",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Outcome::Victory => "Every objective is complete, the colony is thriving",
+            Outcome::TownCentreDestroyed => "The town centre was destroyed",
+            Outcome::Starved => "Everyone starved",
+        }
+    }
+}
+
+/// The objective being worked on, how far along it is and how the game
+/// ended once it has
+#[derive(Resource, Default)]
+pub struct Objectives {
+    /// Index into `Objective::ORDER`
+    current: usize,
+    progress: u32,
+    outcome: Option<Outcome>,
+}
+
+impl Objectives {
+    pub fn current(&self) -> Option<Objective> {
+        Objective::ORDER.get(self.current).copied()
+    }
+
+    /// Objectives completed so far
+    pub fn completed(&self) -> usize {
+        self.current
+    }
+
+    /// Progress towards the current objective
+    pub fn progress(&self) -> u32 {
+        self.progress
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Updates the progress of the current objective, moving on to the next
+    /// one for as long as they are already complete
+    #[allow(clippy::type_complexity)]
+    fn track(
+        mut objectives: ResMut<Objectives>,
+        stockpile: Res<Stockpile>,
+        raids: Res<Raids>,
+        mut notifications: EventWriter<Notification>,
+        town_centre_query: Query<(), With<TownCentre>>,
+        population_query: Query<(), Or<(With<Worker>, With<Soldier>)>>,
+    ) {
+        if objectives.outcome.is_some() || town_centre_query.is_empty() {
+            return;
+        }
+        let population = population_query.iter().count() as u32;
+
+        while let Some(objective) = objectives.current() {
+            let progress = objective.progress(population, &stockpile, &raids);
+            if objectives.progress != progress {
+                objectives.progress = progress;
+            }
+            if progress < objective.target() {
+                return;
+            }
+
+            notifications.send(Notification::new(
+                Severity::Good,
+                format!("Objective complete: {}", objective.label()),
+            ));
+            objectives.current += 1;
+            objectives.progress = 0;
+        }
+
+        objectives.outcome = Some(Outcome::Victory);
+    }
+
+    /// The game is lost when the town centre is destroyed, or once it stands
+    /// with no one left and no food to bring anyone back
+    #[allow(clippy::type_complexity)]
+    fn check_defeat(
+        mut objectives: ResMut<Objectives>,
+        mut destroyed: EventReader<BuildingDestroyed>,
+        stockpile: Res<Stockpile>,
+        town_centre_query: Query<(), With<TownCentre>>,
+        population_query: Query<(), Or<(With<Worker>, With<Soldier>)>>,
+    ) {
+        if objectives.outcome.is_some() {
+            destroyed.clear();
+            return;
+        }
+
+        if destroyed
+            .iter()
+            .any(|destroyed| destroyed.building == BuildingType::TownCentre)
+        {
+            objectives.outcome = Some(Outcome::TownCentreDestroyed);
+        } else if !town_centre_query.is_empty()
+            && population_query.is_empty()
+            && stockpile.amount(Good::Food) == 0
+        {
+            objectives.outcome = Some(Outcome::Starved);
+        }
+    }
+
+    fn end_game(objectives: Res<Objectives>, mut state: ResMut<State<GameState>>) {
+        if objectives.outcome.is_some() {
+            state.set(GameState::GameOver).unwrap();
+        }
+    }
+}
+
+/// Tracks objectives and ends the game, works without a window. Progress and
+/// the end screen are shown by `ObjectivesPanelPlugin`.
+pub struct ObjectivesPlugin;
+
+impl Plugin for ObjectivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Objectives>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(Objectives::track)
+                .with_system(Objectives::check_defeat.after(Objectives::track))
+                .with_system(Objectives::end_game.after(Objectives::check_defeat)),
+        );
+    }
+}
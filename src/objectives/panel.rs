@@ -0,0 +1,192 @@
+//! The current objective and its progress in the top right corner, and the
+//! victory or defeat screen once the game is over.
+
+use bevy::{app::AppExit, prelude::*};
+
+use super::{Objective, Objectives};
+use crate::{director::EventDirector, loading::FontAssets, units::Raids, GameState};
+
+const QUIT_COLOUR: Color = Color::rgb(0.21, 0.73, 0.95);
+const QUIT_HOVERED_COLOUR: Color = Color::rgb(1.0, 1.0, 0.88);
+
+#[derive(Component)]
+struct ObjectiveText;
+
+#[derive(Component)]
+struct QuitButton;
+
+pub struct ObjectivesPanelPlugin;
+
+impl Plugin for ObjectivesPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Playing).with_system(ObjectivesPanelPlugin::spawn),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(ObjectivesPanelPlugin::update_progress),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(ObjectivesPanelPlugin::spawn_end_screen),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::GameOver)
+                .with_system(ObjectivesPanelPlugin::click_quit),
+        );
+    }
+}
+
+impl ObjectivesPanelPlugin {
+    fn text_style(fonts: &FontAssets, font_size: f32) -> TextStyle {
+        TextStyle {
+            font: fonts.fira_sans.clone(),
+            font_size,
+            color: Color::WHITE,
+        }
+    }
+
+    fn spawn(mut commands: Commands, fonts: Res<FontAssets>) {
+        commands.spawn((
+            ObjectiveText,
+            TextBundle {
+                text: Text::from_section("", ObjectivesPanelPlugin::text_style(&fonts, 20.)),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect::new(
+                        Val::Undefined,
+                        Val::Px(10.),
+                        Val::Px(10.),
+                        Val::Undefined,
+                    ),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+
+    fn update_progress(
+        objectives: Res<Objectives>,
+        added_query: Query<(), Added<ObjectiveText>>,
+        mut text_query: Query<&mut Text, With<ObjectiveText>>,
+    ) {
+        if !objectives.is_changed() && added_query.is_empty() {
+            return;
+        }
+
+        let progress = match objectives.current() {
+            Some(objective) => format!(
+                "Objective {}/{}: {} ({}/{})",
+                objectives.completed() + 1,
+                Objective::ORDER.len(),
+                objective.label(),
+                objectives.progress(),
+                objective.target(),
+            ),
+            None => "Every objective is complete".to_string(),
+        };
+
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = progress.clone();
+        }
+    }
+
+    fn spawn_end_screen(
+        mut commands: Commands,
+        fonts: Res<FontAssets>,
+        objectives: Res<Objectives>,
+        director: Res<EventDirector>,
+        raids: Res<Raids>,
+    ) {
+        let Some(outcome) = objectives.outcome() else {
+            return;
+        };
+        let summary = format!(
+            "Day {}   Objectives {}/{}   Raids survived {}",
+            director.day,
+            objectives.completed(),
+            Objective::ORDER.len(),
+            raids.survived,
+        );
+
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(
+                    TextBundle::from_section(
+                        outcome.title(),
+                        ObjectivesPanelPlugin::text_style(&fonts, 64.),
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(20.)),
+                        ..default()
+                    }),
+                );
+                parent.spawn(TextBundle::from_section(
+                    outcome.description(),
+                    ObjectivesPanelPlugin::text_style(&fonts, 28.),
+                ));
+                parent.spawn(
+                    TextBundle::from_section(
+                        summary,
+                        ObjectivesPanelPlugin::text_style(&fonts, 18.),
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(12.)),
+                        ..default()
+                    }),
+                );
+
+                parent
+                    .spawn((
+                        QuitButton,
+                        ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(200.0), Val::Px(60.0)),
+                                margin: UiRect::all(Val::Px(20.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: QUIT_COLOUR.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        let mut style = ObjectivesPanelPlugin::text_style(&fonts, 32.);
+                        style.color = Color::BLACK;
+                        parent.spawn(TextBundle::from_section("Quit", style));
+                    });
+            });
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn click_quit(
+        mut exit: EventWriter<AppExit>,
+        mut button_query: Query<
+            (&Interaction, &mut BackgroundColor),
+            (Changed<Interaction>, With<QuitButton>),
+        >,
+    ) {
+        for (interaction, mut colour) in button_query.iter_mut() {
+            match *interaction {
+                Interaction::Clicked => exit.send(AppExit),
+                Interaction::Hovered => *colour = QUIT_HOVERED_COLOUR.into(),
+                Interaction::None => *colour = QUIT_COLOUR.into(),
+            }
+        }
+    }
+}
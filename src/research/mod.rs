@@ -0,0 +1,261 @@
+//! Research. Techs are paid for from the stockpile and take a while to
+//! research, one at a time, and each unlocks buildings or upgrades. Most
+//! need other techs first, which makes up the tree.
+
+use std::{collections::HashSet, str::FromStr};
+
+use bevy::prelude::*;
+
+use crate::{
+    building::BuildingType,
+    economy::{Good, Goods, Stockpile},
+    notifications::{Notification, Severity},
+    GameState,
+};
+
+mod panel;
+
+pub use panel::ResearchPanelPlugin;
+
+/// Buildings that can be built without any research
+const ALWAYS_UNLOCKED: [BuildingType; 4] = [
+    BuildingType::TownCentre,
+    BuildingType::Road,
+    BuildingType::Farm,
+    BuildingType::House,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Tech {
+    Forestry,
+    Masonry,
+    Trade,
+    Carpentry,
+    Militia,
+    Architecture,
+}
+
+impl Tech {
+    pub const ALL: [Tech; 6] = [
+        Tech::Forestry,
+        Tech::Masonry,
+        Tech::Trade,
+        Tech::Carpentry,
+        Tech::Militia,
+        Tech::Architecture,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tech::Forestry => "Forestry",
+            Tech::Masonry => "Masonry",
+            Tech::Trade => "Trade",
+            Tech::Carpentry => "Carpentry",
+            Tech::Militia => "Militia",
+            Tech::Architecture => "Architecture",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Tech::Forestry => "Lumber mills saw wood into planks",
+            Tech::Masonry => "Stonecutters cut stone into blocks",
+            Tech::Trade => "Stalls trade goods and shades decorate the town",
+            Tech::Carpentry => "Houses get a second storey",
+            Tech::Militia => "Barracks train soldiers",
+            Tech::Architecture => "Houses grow to their largest",
+        }
+    }
+
+    /// Techs that have to be researched first
+    pub fn requires(&self) -> &'static [Tech] {
+        match self {
+            Tech::Forestry => &[],
+            Tech::Masonry | Tech::Trade | Tech::Carpentry => &[Tech::Forestry],
+            Tech::Militia => &[Tech::Masonry],
+            Tech::Architecture => &[Tech::Carpentry, Tech::Masonry],
+        }
+    }
+
+    /// Buildings that can be built, or upgraded to, once it is researched
+    pub fn unlocks(&self) -> &'static [BuildingType] {
+        match self {
+            Tech::Forestry => &[BuildingType::LumberMill],
+            Tech::Masonry => &[BuildingType::Stonecutter],
+            Tech::Trade => &[BuildingType::Stall, BuildingType::Shade],
+            Tech::Carpentry => &[BuildingType::HouseOne],
+            Tech::Militia => &[BuildingType::Barracks],
+            Tech::Architecture => &[BuildingType::HouseTwo],
+        }
+    }
+
+    pub fn cost(&self) -> &'static Goods {
+        match self {
+            Tech::Forestry => &[(Good::Wood, 30)],
+            Tech::Masonry => &[(Good::Wood, 20), (Good::Stone, 20)],
+            Tech::Trade => &[(Good::Wood, 30), (Good::Planks, 10)],
+            Tech::Carpentry => &[(Good::Planks, 20)],
+            Tech::Militia => &[(Good::Wood, 40), (Good::Stone, 20)],
+            Tech::Architecture => &[(Good::Planks, 20), (Good::Blocks, 20)],
+        }
+    }
+
+    /// How long it takes to research once paid for
+    pub fn seconds(&self) -> f32 {
+        match self {
+            Tech::Forestry => 20.0,
+            Tech::Masonry | Tech::Trade => 30.0,
+            Tech::Carpentry => 40.0,
+            Tech::Militia => 45.0,
+            Tech::Architecture => 60.0,
+        }
+    }
+
+    /// How deep in the tree it is, techs without requirements are tier 0
+    pub fn tier(&self) -> usize {
+        self.requires()
+            .iter()
+            .map(|tech| tech.tier() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl FromStr for Tech {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        Tech::ALL
+            .into_iter()
+            .find(|tech| tech.label().eq_ignore_ascii_case(label))
+            .ok_or_else(|| format!("unknown tech {}", label))
+    }
+}
+
+/// Asks for `tech` to be researched, ignored while something else is being
+/// researched, when its requirements are missing or the stockpile can't pay
+pub struct StartResearch {
+    pub tech: Tech,
+}
+
+/// Sent when a tech has been researched
+pub struct ResearchFinished {
+    pub tech: Tech,
+}
+
+/// What has been researched and what is being researched now
+#[derive(Resource, Default)]
+pub struct Research {
+    researched: HashSet<Tech>,
+    current: Option<(Tech, Timer)>,
+}
+
+impl Research {
+    pub fn is_researched(&self, tech: Tech) -> bool {
+        self.researched.contains(&tech)
+    }
+
+    /// Whether its requirements are met and it hasn't been researched yet
+    pub fn is_available(&self, tech: Tech) -> bool {
+        !self.is_researched(tech)
+            && tech
+                .requires()
+                .iter()
+                .all(|required| self.is_researched(*required))
+    }
+
+    pub fn is_unlocked(&self, building: BuildingType) -> bool {
+        ALWAYS_UNLOCKED.contains(&building)
+            || self
+                .researched
+                .iter()
+                .any(|tech| tech.unlocks().contains(&building))
+    }
+
+    pub fn current(&self) -> Option<Tech> {
+        self.current.as_ref().map(|(tech, _)| *tech)
+    }
+
+    /// How far along the current research is, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.current
+            .as_ref()
+            .map_or(0.0, |(_, timer)| timer.percent())
+    }
+
+    /// Every tech researched so far, in tree order
+    pub fn researched(&self) -> impl Iterator<Item = Tech> + '_ {
+        Tech::ALL
+            .into_iter()
+            .filter(|tech| self.is_researched(*tech))
+    }
+
+    /// Marks `tech` as researched straight away, for loading saves
+    pub fn complete(&mut self, tech: Tech) {
+        self.researched.insert(tech);
+        if self.current() == Some(tech) {
+            self.current = None;
+        }
+    }
+
+    fn start(
+        mut events: EventReader<StartResearch>,
+        mut research: ResMut<Research>,
+        mut stockpile: ResMut<Stockpile>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        for StartResearch { tech } in events.iter() {
+            if research.current.is_some() || !research.is_available(*tech) {
+                continue;
+            }
+            if !stockpile.spend(tech.cost()) {
+                continue;
+            }
+
+            research.current = Some((*tech, Timer::from_seconds(tech.seconds(), TimerMode::Once)));
+            notifications.send(Notification::new(
+                Severity::Info,
+                format!("Researching {}", tech.label()),
+            ));
+        }
+    }
+
+    fn advance(
+        time: Res<Time>,
+        mut research: ResMut<Research>,
+        mut finished: EventWriter<ResearchFinished>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        let Some((tech, timer)) = research.current.as_mut() else {
+            return;
+        };
+        if !timer.tick(time.delta()).finished() {
+            return;
+        }
+
+        let tech = *tech;
+        research.complete(tech);
+        finished.send(ResearchFinished { tech });
+        notifications.send(Notification::new(
+            Severity::Good,
+            format!("{} researched: {}", tech.label(), tech.description()),
+        ));
+    }
+}
+
+/// Keeps track of research, works without a window. The research tree is
+/// shown by `ResearchPanelPlugin`.
+pub struct ResearchPlugin;
+
+impl Plugin for ResearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartResearch>()
+            .add_event::<ResearchFinished>()
+            .init_resource::<Research>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(Research::start)
+                    .with_system(Research::advance.after(Research::start)),
+            );
+    }
+}
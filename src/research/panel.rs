@@ -0,0 +1,260 @@
+//! The research tree, toggled with E. Techs are laid out in columns by how
+//! deep in the tree they are, clicking one that is available researches it.
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use super::{Research, StartResearch, Tech};
+use crate::{economy::Stockpile, loading::FontAssets, GameState};
+
+const TECH_WIDTH: f32 = 220.0;
+const RESEARCHED_COLOUR: Color = Color::rgba(0.15, 0.4, 0.2, 0.9);
+const RESEARCHING_COLOUR: Color = Color::rgba(0.15, 0.3, 0.55, 0.9);
+const AVAILABLE_COLOUR: Color = Color::rgba(0.3, 0.3, 0.35, 0.9);
+/// Needs other techs first, or more goods than the stockpile holds
+const LOCKED_COLOUR: Color = Color::rgba(0.15, 0.15, 0.15, 0.9);
+
+#[derive(Component)]
+struct ResearchPanel;
+
+#[derive(Component)]
+struct ResearchProgressText;
+
+#[derive(Component)]
+struct TechBtn(Tech);
+
+#[derive(Component)]
+struct TechBtnText(Tech);
+
+/// The mouse driven side of research, what has been researched is kept by
+/// `ResearchPlugin`
+pub struct ResearchPanelPlugin;
+
+impl Plugin for ResearchPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Playing).with_system(ResearchPanelPlugin::spawn),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(ResearchPanelPlugin::toggle)
+                .with_system(ResearchPanelPlugin::update_tree)
+                .with_system(ResearchPanelPlugin::on_tech_btn_click),
+        );
+    }
+}
+
+impl ResearchPanelPlugin {
+    fn text_style(fonts: &FontAssets, font_size: f32) -> TextStyle {
+        TextStyle {
+            font: fonts.fira_sans.clone(),
+            font_size,
+            color: Color::WHITE,
+        }
+    }
+
+    fn tech_button() -> ButtonBundle {
+        ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(TECH_WIDTH), Val::Auto),
+                padding: UiRect::all(Val::Px(8.)),
+                margin: UiRect::all(Val::Px(6.)),
+                ..default()
+            },
+            background_color: LOCKED_COLOUR.into(),
+            ..default()
+        }
+    }
+
+    fn spawn_tech(parent: &mut ChildBuilder, fonts: &FontAssets, tech: Tech) {
+        parent
+            .spawn((TechBtn(tech), ResearchPanelPlugin::tech_button()))
+            .with_children(|parent| {
+                parent.spawn((
+                    TechBtnText(tech),
+                    TextBundle::from_section("", ResearchPanelPlugin::text_style(fonts, 14.))
+                        .with_style(Style {
+                            max_size: Size::new(Val::Px(TECH_WIDTH - 16.), Val::Undefined),
+                            ..default()
+                        }),
+                ));
+            });
+    }
+
+    fn spawn(mut commands: Commands, fonts: Res<FontAssets>) {
+        let tiers = Tech::ALL.iter().map(Tech::tier).max().unwrap_or(0) + 1;
+
+        commands
+            .spawn((
+                ResearchPanel,
+                NodeBundle {
+                    style: Style {
+                        display: Display::None,
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    focus_policy: FocusPolicy::Pass,
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            padding: UiRect::all(Val::Px(12.)),
+                            ..default()
+                        },
+                        background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Research   <e> to close",
+                            ResearchPanelPlugin::text_style(&fonts, 24.),
+                        ));
+                        parent.spawn((
+                            ResearchProgressText,
+                            TextBundle::from_section(
+                                "",
+                                ResearchPanelPlugin::text_style(&fonts, 16.),
+                            ),
+                        ));
+
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    margin: UiRect::new(
+                                        Val::Px(0.),
+                                        Val::Px(0.),
+                                        Val::Px(8.),
+                                        Val::Px(0.),
+                                    ),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                for tier in 0..tiers {
+                                    parent
+                                        .spawn(NodeBundle {
+                                            style: Style {
+                                                flex_direction: FlexDirection::Column,
+                                                ..default()
+                                            },
+                                            ..default()
+                                        })
+                                        .with_children(|parent| {
+                                            for tech in Tech::ALL
+                                                .into_iter()
+                                                .filter(|tech| tech.tier() == tier)
+                                            {
+                                                ResearchPanelPlugin::spawn_tech(
+                                                    parent, &fonts, tech,
+                                                );
+                                            }
+                                        });
+                                }
+                            });
+                    });
+            });
+    }
+
+    fn toggle(keys: Res<Input<KeyCode>>, mut panel_query: Query<&mut Style, With<ResearchPanel>>) {
+        if !keys.just_pressed(KeyCode::E) {
+            return;
+        }
+
+        for mut style in panel_query.iter_mut() {
+            style.display = match style.display {
+                Display::None => Display::Flex,
+                Display::Flex => Display::None,
+            };
+        }
+    }
+
+    /// What each tech does, what it costs or needs, and how far along the
+    /// current research is
+    #[allow(clippy::type_complexity)]
+    fn update_tree(
+        research: Res<Research>,
+        stockpile: Res<Stockpile>,
+        added_query: Query<(), Added<ResearchPanel>>,
+        mut progress_query: Query<&mut Text, (With<ResearchProgressText>, Without<TechBtnText>)>,
+        mut text_query: Query<(&TechBtnText, &mut Text)>,
+        mut btn_query: Query<(&TechBtn, &mut BackgroundColor)>,
+    ) {
+        if !research.is_changed() && !stockpile.is_changed() && added_query.is_empty() {
+            return;
+        }
+
+        let progress = match research.current() {
+            Some(tech) => format!(
+                "Researching {} {:.0}%",
+                tech.label(),
+                research.progress() * 100.0
+            ),
+            None => "Pick something to research".to_string(),
+        };
+        for mut text in progress_query.iter_mut() {
+            text.sections[0].value = progress.clone();
+        }
+
+        for (TechBtnText(tech), mut text) in text_query.iter_mut() {
+            let status = if research.is_researched(*tech) {
+                "Researched".to_string()
+            } else if research.current() == Some(*tech) {
+                format!("Researching {:.0}%", research.progress() * 100.0)
+            } else if research.is_available(*tech) {
+                let cost = tech
+                    .cost()
+                    .iter()
+                    .map(|(good, amount)| format!("{} {}", good.label(), amount))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{}, {}s", cost, tech.seconds())
+            } else {
+                let needs = tech
+                    .requires()
+                    .iter()
+                    .filter(|required| !research.is_researched(**required))
+                    .map(|required| required.label())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("Needs {}", needs)
+            };
+
+            text.sections[0].value =
+                format!("{}\n{}\n{}", tech.label(), tech.description(), status);
+        }
+
+        for (TechBtn(tech), mut colour) in btn_query.iter_mut() {
+            let affordable = research.current().is_none()
+                && research.is_available(*tech)
+                && stockpile.can_afford(tech.cost());
+
+            *colour = if research.is_researched(*tech) {
+                RESEARCHED_COLOUR.into()
+            } else if research.current() == Some(*tech) {
+                RESEARCHING_COLOUR.into()
+            } else if affordable {
+                AVAILABLE_COLOUR.into()
+            } else {
+                LOCKED_COLOUR.into()
+            };
+        }
+    }
+
+    fn on_tech_btn_click(
+        interaction_query: Query<(&Interaction, &TechBtn), Changed<Interaction>>,
+        mut events: EventWriter<StartResearch>,
+    ) {
+        for (interaction, TechBtn(tech)) in interaction_query.iter() {
+            if *interaction == Interaction::Clicked {
+                events.send(StartResearch { tech: *tech });
+            }
+        }
+    }
+}
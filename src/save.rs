@@ -0,0 +1,253 @@
+//! Save files: the settings the world was generated with, the stockpile, what
+//! has been researched and every placed building, as plain `key value` lines.
+//! The world itself is regenerated from the seed when loading.
+//!
+//! ```text
+//! seed 42
+//! cols 64
+//! rows 32
+//! block_size 64
+//! resources normal
+//! water normal
+//! world fixed
+//! stock wood 130
+//! research forestry
+//! building TownCentre 31 15 north
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    building::{BuildingType, Facing, PlaceBuilding},
+    cli::LaunchOptions,
+    economy::{Good, Stockpile},
+    global_state::GlobalState,
+    research::{Research, Tech},
+    tiles::TilePosition,
+    world_gen::WorldMap,
+    GameState,
+};
+
+/// Quick saves go here unless a save was loaded on the command line
+const DEFAULT_SAVE_FILE: &str = "savegame.txt";
+
+pub struct SaveGame {
+    pub global_state: GlobalState,
+    pub stockpile: Vec<(Good, u32)>,
+    pub research: Vec<Tech>,
+    pub buildings: Vec<(BuildingType, IVec2, Facing)>,
+}
+
+impl SaveGame {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+
+        SaveGame::parse(&text).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut global_state = GlobalState::default();
+        let mut stockpile = vec![];
+        let mut research = vec![];
+        let mut buildings = vec![];
+
+        for (number, line) in text.lines().enumerate() {
+            let mut words = line.split_whitespace();
+            let Some(key) = words.next() else {
+                continue;
+            };
+            let values = words.collect::<Vec<_>>();
+            let value = |index: usize| {
+                values
+                    .get(index)
+                    .copied()
+                    .ok_or_else(|| format!("line {}: {} is missing a value", number + 1, key))
+            };
+            let number_error = |_| format!("line {}: {} expects a number", number + 1, key);
+
+            match key {
+                "seed" => global_state.seed = value(0)?.parse().map_err(number_error)?,
+                "cols" => global_state.world_cols = value(0)?.parse().map_err(number_error)?,
+                "rows" => global_state.world_rows = value(0)?.parse().map_err(number_error)?,
+                "block_size" => {
+                    global_state.block_size = value(0)?.parse().map_err(number_error)?
+                }
+                "resources" => global_state.resource_abundance = value(0)?.parse()?,
+                "water" => global_state.water_level = value(0)?.parse()?,
+                "world" => global_state.world_mode = value(0)?.parse()?,
+                "stock" => {
+                    let good = value(0)?.parse()?;
+                    let amount = value(1)?.parse().map_err(number_error)?;
+                    stockpile.push((good, amount));
+                }
+                "research" => research.push(value(0)?.parse()?),
+                "building" => {
+                    let building = value(0)?.parse()?;
+                    let x = value(1)?.parse().map_err(number_error)?;
+                    let y = value(2)?.parse().map_err(number_error)?;
+                    // Saves from before buildings could turn leave it out
+                    let facing = match values.get(3) {
+                        Some(facing) => facing.parse()?,
+                        None => Facing::North,
+                    };
+                    buildings.push((building, IVec2::new(x, y), facing));
+                }
+                other => return Err(format!("line {}: unknown key {}", number + 1, other)),
+            }
+        }
+
+        Ok(Self {
+            global_state,
+            stockpile,
+            research,
+            buildings,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let state = &self.global_state;
+        let mut text = format!(
+            "seed {}\ncols {}\nrows {}\nblock_size {}\nresources {}\nwater {}\nworld {}\n",
+            state.seed,
+            state.world_cols,
+            state.world_rows,
+            state.block_size,
+            state.resource_abundance.label().to_lowercase(),
+            state.water_level.label().to_lowercase(),
+            state.world_mode.label().to_lowercase(),
+        );
+
+        for (good, amount) in &self.stockpile {
+            text.push_str(&format!(
+                "stock {} {}\n",
+                good.label().to_lowercase(),
+                amount
+            ));
+        }
+
+        for tech in &self.research {
+            text.push_str(&format!("research {}\n", tech.label().to_lowercase()));
+        }
+
+        for (building, tile, facing) in &self.buildings {
+            text.push_str(&format!(
+                "building {} {} {} {}\n",
+                building.name(),
+                tile.x,
+                tile.y,
+                facing.label()
+            ));
+        }
+
+        text
+    }
+}
+
+/// The file quick saves are written to
+#[derive(Resource)]
+struct SavePath(PathBuf);
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Playing).with_system(SavePlugin::setup))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(SavePlugin::quick_save)
+                    .with_system(SavePlugin::restore_buildings),
+            );
+    }
+}
+
+impl SavePlugin {
+    fn setup(mut commands: Commands, launch_options: Res<LaunchOptions>) {
+        let path = match &launch_options.load {
+            Some((path, _)) => path.clone(),
+            None => PathBuf::from(DEFAULT_SAVE_FILE),
+        };
+
+        commands.insert_resource(SavePath(path));
+    }
+
+    /// F5 writes the current game to the save file
+    fn quick_save(
+        keys: Res<Input<KeyCode>>,
+        save_path: Res<SavePath>,
+        global_state: Res<GlobalState>,
+        stockpile: Res<Stockpile>,
+        research: Res<Research>,
+        building_query: Query<(&BuildingType, &TilePosition, &Facing)>,
+    ) {
+        if !keys.just_pressed(KeyCode::F5) {
+            return;
+        }
+
+        let save = SaveGame {
+            global_state: global_state.clone(),
+            stockpile: Good::ALL
+                .into_iter()
+                .map(|good| (good, stockpile.amount(good)))
+                .collect(),
+            research: research.researched().collect(),
+            buildings: building_query
+                .iter()
+                .map(|(building, tile, facing)| (*building, tile.0, *facing))
+                .collect(),
+        };
+
+        match fs::write(&save_path.0, save.to_text()) {
+            Ok(_) => info!("Saved the game to {}", save_path.0.display()),
+            Err(error) => error!("Could not save to {}: {}", save_path.0.display(), error),
+        }
+    }
+
+    /// Places the buildings of a loaded save once the world has been generated
+    /// and fills the stockpile and research back up
+    fn restore_buildings(
+        mut launch_options: ResMut<LaunchOptions>,
+        mut stockpile: ResMut<Stockpile>,
+        mut research: ResMut<Research>,
+        map: Option<Res<WorldMap>>,
+        mut event: EventWriter<PlaceBuilding>,
+    ) {
+        if map.is_none() {
+            return;
+        }
+        let Some((_, save)) = launch_options.load.as_mut() else {
+            return;
+        };
+
+        // Buildings that claim territory go first, so the rest land inside it
+        save.buildings.sort_by_key(|(building, _, _)| {
+            (
+                *building != BuildingType::TownCentre,
+                building.territory_radius().is_none(),
+            )
+        });
+
+        for (good, amount) in save.stockpile.drain(..) {
+            stockpile.set(good, amount);
+        }
+
+        for tech in save.research.drain(..) {
+            research.complete(tech);
+        }
+
+        for (building, tile, facing) in save.buildings.drain(..) {
+            event.send(PlaceBuilding {
+                building,
+                tile,
+                facing,
+                free: true,
+                record: false,
+            });
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::loading::TextureAssets;
 use bevy::prelude::*;
 use rand::Rng;
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum TileType {
     Dirt,
     Grass,
@@ -22,6 +22,38 @@ impl TileType {
         }
     }
 
+    pub fn label(&self) -> &'static str {
+        match self {
+            TileType::Dirt => "dirt",
+            TileType::Grass => "grass",
+            TileType::Sand => "sand",
+            TileType::Stone => "stone",
+            TileType::Water => "water",
+        }
+    }
+
+    /// Flat colour used when drawing the map without textures
+    pub fn map_colour(&self) -> Color {
+        match self {
+            TileType::Dirt => Color::rgb_u8(130, 95, 60),
+            TileType::Grass => Color::rgb_u8(95, 160, 70),
+            TileType::Sand => Color::rgb_u8(225, 205, 140),
+            TileType::Stone => Color::rgb_u8(150, 150, 150),
+            TileType::Water => Color::rgb_u8(50, 110, 200),
+        }
+    }
+
+    /// Where the tile sits when blending terrain, higher tiles fade over the edges of lower ones
+    pub fn blend_priority(&self) -> u8 {
+        match self {
+            TileType::Water => 0,
+            TileType::Sand => 1,
+            TileType::Dirt => 2,
+            TileType::Grass => 3,
+            TileType::Stone => 4,
+        }
+    }
+
     pub fn from_perlin(value: &f32) -> Self {
         let as_percentage: i32 = (value.abs() * 100.) as i32;
         match as_percentage {
@@ -41,6 +73,44 @@ pub struct Tile(pub TileType);
 #[derive(Component)]
 pub struct ResourceTile;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResourceKind {
+    Tree,
+    Stone,
+    Berry,
+}
+
+impl ResourceKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResourceKind::Tree => "trees",
+            ResourceKind::Stone => "rocks",
+            ResourceKind::Berry => "berries",
+        }
+    }
+
+    /// Flat colour used when drawing the map without textures
+    pub fn map_colour(&self) -> Color {
+        match self {
+            ResourceKind::Tree => Color::rgb_u8(35, 90, 35),
+            ResourceKind::Stone => Color::rgb_u8(90, 90, 100),
+            ResourceKind::Berry => Color::rgb_u8(180, 50, 90),
+        }
+    }
+
+    pub fn roll_lvl(&self, rng: &mut impl Rng) -> usize {
+        match self {
+            ResourceKind::Tree => rng.gen_range(1..=4),
+            ResourceKind::Stone => rng.gen_range(1..=3),
+            ResourceKind::Berry => rng.gen_range(1..=2),
+        }
+    }
+}
+
+/// Map coordinate of a tile, or of the tile something was placed on
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TilePosition(pub IVec2);
+
 pub trait ResourceItem {
     fn texture(&self, textures: &TextureAssets) -> Handle<Image>;
     fn yield_amount(&self) -> usize;
@@ -1,11 +1,30 @@
+use std::collections::HashMap;
+
 use bevy::{prelude::*, ui::FocusPolicy};
 
 use crate::{
     building::{BuildingModeChange, BuildingState, BuildingType, TownCentreBuilt},
+    economy::{BuyGoods, Good, Market, Stockpile, BUY_AMOUNT},
     loading::{BuildingAssets, FontAssets},
+    research::{Research, ResearchFinished},
     GameState,
 };
 
+/// Buttons of the build panel once the town centre stands, in this order
+/// and as far as they have been researched
+const BUILD_BUTTONS: [BuildingType; 10] = [
+    BuildingType::Barracks,
+    BuildingType::Farm,
+    BuildingType::House,
+    BuildingType::HouseOne,
+    BuildingType::HouseTwo,
+    BuildingType::Shade,
+    BuildingType::Stall,
+    BuildingType::LumberMill,
+    BuildingType::Stonecutter,
+    BuildingType::Road,
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
 pub enum GuiPluginLabels {
     EnterBuildingMode,
@@ -25,7 +44,10 @@ impl Plugin for GuiPlugin {
                     .with_system(
                         GuiPlugin::on_building_btn_click.label(GuiPluginLabels::EnterBuildingMode),
                     )
-                    .with_system(GuiPlugin::on_town_centre_built),
+                    .with_system(GuiPlugin::update_building_btns)
+                    .with_system(GuiPlugin::update_stockpile)
+                    .with_system(GuiPlugin::on_market_btn_click)
+                    .with_system(GuiPlugin::update_market),
             );
     }
 }
@@ -37,10 +59,23 @@ struct Panel;
 struct MainPanel;
 
 #[derive(Component)]
-struct TownCentreBtn;
+struct BuildingBtn(BuildingType);
+
+#[derive(Component)]
+struct StockpileText;
 
 #[derive(Component)]
-struct BuildingBtn(BuildingType);
+struct MarketText;
+
+/// Holds the buy buttons, hidden while the market is closed
+#[derive(Component)]
+struct MarketButtons;
+
+#[derive(Component)]
+struct MarketBtn(Good);
+
+#[derive(Component)]
+struct MarketBtnText(Good);
 
 impl GuiPlugin {
     fn root() -> NodeBundle {
@@ -107,6 +142,62 @@ impl GuiPlugin {
         }
     }
 
+    fn stockpile_text(fonts: &FontAssets) -> TextBundle {
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: fonts.fira_sans.clone(),
+                    font_size: 20.,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(10.), Val::Undefined, Val::Px(10.), Val::Undefined),
+                ..default()
+            },
+            ..default()
+        }
+    }
+
+    fn market_panel() -> NodeBundle {
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(10.), Val::Undefined, Val::Px(40.), Val::Undefined),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            focus_policy: FocusPolicy::Pass,
+            ..default()
+        }
+    }
+
+    fn market_text(fonts: &FontAssets, font_size: f32) -> TextBundle {
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: fonts.fira_sans.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+        )
+    }
+
+    fn market_button() -> ButtonBundle {
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(4.)),
+                margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(4.), Val::Px(0.)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        }
+    }
+
     fn build_building_button(handle: Handle<Image>) -> ButtonBundle {
         ButtonBundle {
             style: Style {
@@ -124,6 +215,38 @@ impl GuiPlugin {
     }
 
     fn spawn(mut commands: Commands, textures: Res<BuildingAssets>, fonts: Res<FontAssets>) {
+        commands.spawn((StockpileText, GuiPlugin::stockpile_text(&fonts)));
+
+        commands
+            .spawn(GuiPlugin::market_panel())
+            .with_children(|parent| {
+                parent.spawn((MarketText, GuiPlugin::market_text(&fonts, 16.)));
+                parent
+                    .spawn((
+                        MarketButtons,
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::FlexStart,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        for good in Good::ALL.into_iter().filter(Good::is_traded) {
+                            parent
+                                .spawn((MarketBtn(good), GuiPlugin::market_button()))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        MarketBtnText(good),
+                                        GuiPlugin::market_text(&fonts, 14.),
+                                    ));
+                                });
+                        }
+                    });
+            });
+
         commands.spawn(GuiPlugin::root()).with_children(|parent| {
             parent
                 .spawn((Panel, GuiPlugin::main_panel_border()))
@@ -133,7 +256,6 @@ impl GuiPlugin {
                         .with_children(|parent| {
                             parent.spawn(GuiPlugin::toggle_help(&fonts));
                             parent.spawn((
-                                TownCentreBtn,
                                 BuildingBtn(BuildingType::TownCentre),
                                 GuiPlugin::spawn_town_centre_btn(&textures),
                             ));
@@ -170,41 +292,126 @@ impl GuiPlugin {
         }
     }
 
-    fn on_town_centre_built(
-        event: EventReader<TownCentreBuilt>,
-        town_centre_btn: Query<Entity, With<TownCentreBtn>>,
+    /// Swaps the town centre button for the other buildings once it is built,
+    /// and adds buildings as they are researched. Buttons that are already
+    /// there are kept.
+    #[allow(clippy::too_many_arguments)]
+    fn update_building_btns(
+        mut built: EventReader<TownCentreBuilt>,
+        mut researched: EventReader<ResearchFinished>,
+        mut town_centre_built: Local<bool>,
+        research: Res<Research>,
+        btn_query: Query<(Entity, &BuildingBtn)>,
         panel: Query<Entity, With<MainPanel>>,
         mut commands: Commands,
         textures: Res<BuildingAssets>,
     ) {
-        if event.is_empty() {
+        let just_built = built.iter().count() > 0;
+        let just_researched = researched.iter().count() > 0;
+        *town_centre_built |= just_built;
+        if !*town_centre_built || !(just_built || just_researched) {
             return;
         }
 
-        let town_centre_btn = town_centre_btn.single();
-        commands.entity(town_centre_btn).despawn_recursive();
-
-        let panel = panel.single();
-        commands.entity(panel).add_children(|parent| {
-            let buttons: Vec<BuildingType> = vec![
-                BuildingType::Barracks,
-                BuildingType::Farm,
-                BuildingType::House,
-                BuildingType::HouseOne,
-                BuildingType::HouseTwo,
-                BuildingType::Shade,
-                BuildingType::Stall,
-            ];
-
-            for button in buttons {
-                parent.spawn((
-                    BuildingBtn(button),
-                    GuiPlugin::build_building_button(button.get_texture(&textures)),
-                ));
+        let unlocked = BUILD_BUTTONS
+            .into_iter()
+            .filter(|building| research.is_unlocked(*building))
+            .collect::<Vec<_>>();
+        let mut existing = HashMap::new();
+        for (btn, BuildingBtn(building)) in btn_query.iter() {
+            match unlocked.contains(building) {
+                true => {
+                    existing.insert(*building, btn);
+                }
+                false => commands.entity(btn).despawn_recursive(),
             }
-        });
+        }
+
+        // Pushing buttons that are already children moves them to the end,
+        // which keeps every button in the order of `BUILD_BUTTONS`
+        let buttons = unlocked
+            .into_iter()
+            .map(|building| match existing.get(&building) {
+                Some(btn) => *btn,
+                None => commands
+                    .spawn((
+                        BuildingBtn(building),
+                        GuiPlugin::build_building_button(building.get_texture(&textures)),
+                    ))
+                    .id(),
+            })
+            .collect::<Vec<_>>();
+        commands.entity(panel.single()).push_children(&buttons);
+    }
+
+    fn update_stockpile(
+        stockpile: Res<Stockpile>,
+        added_query: Query<(), Added<StockpileText>>,
+        mut text_query: Query<&mut Text, With<StockpileText>>,
+    ) {
+        if !stockpile.is_changed() && added_query.is_empty() {
+            return;
+        }
+
+        let amounts = Good::ALL
+            .iter()
+            .map(|good| format!("{} {}", good.label(), stockpile.amount(*good)))
+            .collect::<Vec<_>>()
+            .join("   ");
 
-        event.clear();
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = amounts.clone();
+        }
+    }
+
+    fn on_market_btn_click(
+        interaction_query: Query<(&Interaction, &MarketBtn), Changed<Interaction>>,
+        mut event: EventWriter<BuyGoods>,
+    ) {
+        for (interaction, btn) in interaction_query.iter() {
+            if *interaction == Interaction::Clicked {
+                event.send(BuyGoods {
+                    good: btn.0,
+                    amount: BUY_AMOUNT,
+                });
+            }
+        }
+    }
+
+    /// Shows whether the market is open and what each good costs right now
+    fn update_market(
+        market: Res<Market>,
+        added_query: Query<(), Added<MarketText>>,
+        mut text_query: Query<&mut Text, With<MarketText>>,
+        mut btn_text_query: Query<(&MarketBtnText, &mut Text), Without<MarketText>>,
+        mut buttons_query: Query<&mut Style, With<MarketButtons>>,
+    ) {
+        if !market.is_changed() && added_query.is_empty() {
+            return;
+        }
+
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = match market.is_open() {
+                true => "Market".to_string(),
+                false => "Market closed, staff a stall to trade".to_string(),
+            };
+        }
+
+        for (btn, mut text) in btn_text_query.iter_mut() {
+            text.sections[0].value = format!(
+                "Buy {} {} for {} Coin",
+                BUY_AMOUNT,
+                btn.0.label(),
+                market.buy_cost(btn.0, BUY_AMOUNT)
+            );
+        }
+
+        for mut style in buttons_query.iter_mut() {
+            style.display = match market.is_open() {
+                true => Display::Flex,
+                false => Display::None,
+            };
+        }
     }
 }
 
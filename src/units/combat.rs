@@ -0,0 +1,98 @@
+//! Fighting between raiders and the colony. Attackers hit their target while
+//! standing next to it, and units whose health runs out are gone. Buildings
+//! that run out of health are torn down by the building plugin.
+
+use bevy::prelude::*;
+
+use super::Health;
+use crate::{
+    building::{Building, BuildingType, Facing},
+    tiles::TilePosition,
+};
+
+/// Seconds between hits
+const ATTACK_SECONDS: f32 = 1.0;
+
+/// A unit that fights, how hard it hits and what it is after
+#[derive(Component)]
+pub struct Attacker {
+    pub damage: u32,
+    pub target: Option<Entity>,
+    cooldown: Timer,
+}
+
+impl Attacker {
+    pub fn new(damage: u32) -> Self {
+        Self {
+            damage,
+            target: None,
+            cooldown: Timer::from_seconds(ATTACK_SECONDS, TimerMode::Repeating),
+        }
+    }
+
+    /// Whether a unit on `from` can hit something covering `tiles`
+    pub fn in_reach(from: IVec2, tiles: &[IVec2]) -> bool {
+        tiles
+            .iter()
+            .any(|tile| (*tile - from).abs().max_element() <= 1)
+    }
+
+    /// Every tile a target covers, buildings can cover more than one
+    pub fn target_tiles(
+        tile: IVec2,
+        building: Option<&BuildingType>,
+        facing: Option<&Facing>,
+    ) -> Vec<IVec2> {
+        match (building, facing) {
+            (Some(building), Some(facing)) => building.tiles(tile, *facing),
+            _ => vec![tile],
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(super) fn strike(
+        time: Res<Time>,
+        mut attacker_query: Query<(&mut Attacker, &TilePosition)>,
+        mut target_query: Query<(
+            &TilePosition,
+            Option<&BuildingType>,
+            Option<&Facing>,
+            &mut Health,
+        )>,
+    ) {
+        for (mut attacker, tile) in attacker_query.iter_mut() {
+            let Some(target) = attacker.target else {
+                continue;
+            };
+            let Ok((target_tile, building, facing, mut health)) = target_query.get_mut(target)
+            else {
+                attacker.target = None;
+                continue;
+            };
+
+            let tiles = Attacker::target_tiles(target_tile.0, building, facing);
+            if !Attacker::in_reach(tile.0, &tiles) || health.current == 0 {
+                continue;
+            }
+
+            if attacker.cooldown.tick(time.delta()).just_finished() {
+                health.damage(attacker.damage);
+            }
+        }
+    }
+}
+
+impl Health {
+    /// Despawns units once their health runs out
+    #[allow(clippy::type_complexity)]
+    pub(super) fn remove_dead(
+        mut commands: Commands,
+        health_query: Query<(Entity, &Health), (Changed<Health>, Without<Building>)>,
+    ) {
+        for (entity, health) in health_query.iter() {
+            if health.current == 0 {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Workers and soldiers eat from the stockpile. When there isn't enough food
+//! to go round, whoever goes without loses health until they starve.
+
+use bevy::prelude::*;
+
+use super::{Health, Soldier, Worker};
+use crate::{
+    economy::{Good, Stockpile},
+    notifications::{Notification, Severity},
+};
+
+/// Seconds between meals
+const MEAL_SECONDS: f32 = 30.0;
+/// Food one unit eats at every meal
+const MEAL: u32 = 1;
+/// Health lost by everyone who went without a meal
+const STARVATION_DAMAGE: u32 = 10;
+
+#[allow(clippy::type_complexity)]
+pub(super) fn eat(
+    time: Res<Time>,
+    mut since_last: Local<f32>,
+    mut was_starving: Local<bool>,
+    mut stockpile: ResMut<Stockpile>,
+    mut notifications: EventWriter<Notification>,
+    mut health_query: Query<&mut Health, Or<(With<Worker>, With<Soldier>)>>,
+) {
+    *since_last += time.delta_seconds();
+    if *since_last < MEAL_SECONDS {
+        return;
+    }
+    *since_last = 0.0;
+
+    let mut starving = 0;
+    for mut health in health_query.iter_mut() {
+        if stockpile.take(Good::Food, MEAL) < MEAL {
+            health.damage(STARVATION_DAMAGE);
+            starving += 1;
+        }
+    }
+
+    if starving > 0 && !*was_starving {
+        notifications.send(Notification::new(
+            Severity::Danger,
+            format!("{} people are starving, there is no food left", starving),
+        ));
+    }
+    *was_starving = starving > 0;
+}
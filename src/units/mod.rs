@@ -0,0 +1,149 @@
+//! People walking around the colony.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    building::RoadNetwork,
+    tiles::{TilePosition, TileType},
+    world_gen::WorldMap,
+    GameState,
+};
+
+mod combat;
+mod hunger;
+mod pathfinding;
+mod raiders;
+mod selection;
+mod soldiers;
+mod workers;
+
+pub use combat::Attacker;
+pub use raiders::{RaidStarted, Raider, Raids};
+pub use selection::{Selected, UnitSelectionPlugin};
+pub use soldiers::{CommandUnits, Soldier, TrainSoldier, UnitOrder, SOLDIER_COST};
+pub use workers::Worker;
+
+/// Tiles a unit walks per second, before any road bonus
+const WALK_SPEED: f32 = 1.5;
+
+/// Hit points of anything that can be attacked
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.current < self.max
+    }
+
+    /// Takes `amount` off, returns whether that left nothing
+    pub fn damage(&mut self, amount: u32) -> bool {
+        self.current = self.current.saturating_sub(amount);
+        self.current == 0
+    }
+}
+
+/// Moves a unit one tile at a time along its path
+#[derive(Component, Default)]
+pub struct Walker {
+    pub path: VecDeque<IVec2>,
+}
+
+impl Walker {
+    pub fn is_idle(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Units can walk anywhere but water and buildings, roads excepted
+    pub fn can_walk(map: &WorldMap, network: &RoadNetwork, tile: IVec2) -> bool {
+        map.get(tile).is_some_and(|map_tile| {
+            map_tile.tile_type != TileType::Water && (!map_tile.occupied || network.is_road(tile))
+        })
+    }
+
+    /// Goes along the row first and then the column to get from `from` to
+    /// `to`, leaving out `from` itself
+    pub fn straight_path(from: IVec2, to: IVec2) -> VecDeque<IVec2> {
+        let mut path = VecDeque::new();
+        let mut tile = from;
+
+        while tile != to {
+            match tile.x != to.x {
+                true => tile.x += (to.x - tile.x).signum(),
+                false => tile.y += (to.y - tile.y).signum(),
+            }
+            path.push_back(tile);
+        }
+
+        path
+    }
+
+    fn walk(
+        time: Res<Time>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        mut walker_query: Query<(&mut Walker, &mut Transform, &mut TilePosition)>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (mut walker, mut transform, mut tile) in walker_query.iter_mut() {
+            let Some(next) = walker.path.front().copied() else {
+                continue;
+            };
+
+            let speed = WALK_SPEED * map.block_size * network.speed_multiplier(tile.0);
+            let step = speed * time.delta_seconds();
+            let target = map.tile_to_world(next).extend(transform.translation.z);
+            let to_target = target - transform.translation;
+
+            if to_target.length() <= step {
+                transform.translation = target;
+                tile.0 = next;
+                walker.path.pop_front();
+            } else {
+                transform.translation += to_target.normalize() * step;
+            }
+        }
+    }
+}
+
+pub struct UnitsPlugin;
+
+impl Plugin for UnitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TrainSoldier>()
+            .add_event::<CommandUnits>()
+            .add_event::<RaidStarted>()
+            .init_resource::<Raids>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(Walker::walk)
+                    .with_system(Worker::arrive_with_town_centre)
+                    .with_system(Worker::wander)
+                    .with_system(Soldier::train)
+                    .with_system(Soldier::follow_orders.after(Soldier::train))
+                    .with_system(Soldier::defend.after(Soldier::follow_orders))
+                    .with_system(Soldier::patrol.after(Soldier::defend))
+                    .with_system(Raids::track_survived)
+                    .with_system(Raids::spawn_waves.after(Raids::track_survived))
+                    .with_system(Raider::hunt)
+                    .with_system(Attacker::strike.after(Raider::hunt).after(Soldier::defend))
+                    .with_system(hunger::eat)
+                    .with_system(
+                        Health::remove_dead
+                            .after(Attacker::strike)
+                            .after(hunger::eat),
+                    ),
+            );
+    }
+}
@@ -0,0 +1,88 @@
+//! A* over the tile grid, shared by every kind of unit. Roads cost less to
+//! walk along, so units go out of their way to use them.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use bevy::prelude::*;
+
+use super::Walker;
+use crate::{building::RoadNetwork, world_gen::WorldMap};
+
+/// Cost of a step onto a road and onto anything else, in the same ratio as
+/// the road speed bonus
+const ROAD_STEP: u32 = 2;
+const STEP: u32 = 3;
+/// Tiles looked at before giving up on a path
+const MAX_SEARCH: usize = 4096;
+
+const SIDES: [IVec2; 4] = [IVec2::Y, IVec2::X, IVec2::NEG_Y, IVec2::NEG_X];
+
+impl Walker {
+    /// The quickest path from `from` to `to`, leaving out `from` itself.
+    /// `to` doesn't have to be walkable, so units can walk up to a building.
+    pub fn find_path(
+        map: &WorldMap,
+        network: &RoadNetwork,
+        from: IVec2,
+        to: IVec2,
+    ) -> Option<VecDeque<IVec2>> {
+        let estimate = |tile: IVec2| {
+            let offset = (to - tile).abs();
+            (offset.x + offset.y) as u32 * ROAD_STEP
+        };
+
+        let mut open = BinaryHeap::from([Reverse((estimate(from), 0, from.x, from.y))]);
+        let mut came_from = HashMap::<IVec2, IVec2>::new();
+        let mut costs = HashMap::from([(from, 0)]);
+
+        while let Some(Reverse((_, cost, x, y))) = open.pop() {
+            let tile = IVec2::new(x, y);
+
+            if tile == to {
+                let mut path = VecDeque::from([tile]);
+                while let Some(previous) = came_from.get(path.front().unwrap()) {
+                    path.push_front(*previous);
+                }
+                path.pop_front();
+
+                return Some(path);
+            }
+            if costs.len() > MAX_SEARCH {
+                return None;
+            }
+            if cost > costs[&tile] {
+                continue;
+            }
+
+            for side in SIDES {
+                let next = tile + side;
+                if next != to && !Walker::can_walk(map, network, next) {
+                    continue;
+                }
+
+                let step = match network.is_road(next) {
+                    true => ROAD_STEP,
+                    false => STEP,
+                };
+                let next_cost = cost + step;
+                if costs.get(&next).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+
+                costs.insert(next, next_cost);
+                came_from.insert(next, tile);
+                open.push(Reverse((
+                    next_cost + estimate(next),
+                    next_cost,
+                    next.x,
+                    next.y,
+                )));
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,342 @@
+//! Raider waves. Once the town centre stands a wave comes every few minutes
+//! from the edge of the map, bigger the larger the colony is and the more
+//! waves came before. Raiders go for whatever they pass on their way to the
+//! town centre, soldiers go for raiders that come near their post.
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{Attacker, Health, Soldier, Walker, Worker};
+use crate::{
+    building::{Building, BuildingType, Facing, Road, RoadNetwork, TownCentre},
+    global_state::WorldMode,
+    loading::PersonAssets,
+    notifications::{Notification, Severity},
+    tiles::TilePosition,
+    world_gen::WorldMap,
+};
+
+/// Seconds from the town centre going up to the first wave
+const FIRST_RAID_SECONDS: f32 = 240.0;
+/// Seconds between waves after that
+const RAID_SECONDS: f32 = 150.0;
+/// Workers, soldiers and buildings per extra raider in a wave
+const COLONY_PER_RAIDER: usize = 6;
+/// How far from the town centre raiders show up in a world without edges
+const STREAMING_RAID_DISTANCE: f32 = 24.0;
+const RAIDER_HEALTH: u32 = 60;
+const RAIDER_DAMAGE: u32 = 8;
+/// How far raiders look for something to attack and soldiers look past their
+/// patrol radius for raiders, in tiles
+const SIGHT: i32 = 4;
+/// Seconds between raiders looking around for a closer target
+const RETARGET_SECONDS: f32 = 1.0;
+const RAIDER_TINT: Color = Color::rgb(0.35, 0.3, 0.3);
+
+#[derive(Component)]
+pub struct Raider {
+    retarget: Timer,
+    /// Nothing next to its target can be walked to, it stays put until it
+    /// looks around again
+    holding: bool,
+}
+
+/// Sent when a wave of raiders shows up
+pub struct RaidStarted {
+    pub wave: u32,
+    pub raiders: usize,
+}
+
+#[derive(Resource)]
+pub struct Raids {
+    /// Waves that have come so far
+    pub waves: u32,
+    /// Waves fought off to the last raider
+    pub survived: u32,
+    in_progress: bool,
+    next: Timer,
+}
+
+impl Default for Raids {
+    fn default() -> Self {
+        Self {
+            waves: 0,
+            survived: 0,
+            in_progress: false,
+            next: Timer::from_seconds(FIRST_RAID_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+impl Raids {
+    pub fn in_progress(&self) -> bool {
+        self.in_progress
+    }
+
+    pub fn seconds_until_next(&self) -> f32 {
+        self.next.remaining_secs()
+    }
+
+    /// Brings the next wave forward to the coming frame
+    pub fn call_next_wave(&mut self) {
+        let duration = self.next.duration();
+        self.next.set_elapsed(duration);
+    }
+
+    /// How many raiders the next wave brings against a colony of
+    /// `colony_size` workers, soldiers and buildings
+    pub fn wave_size(&self, colony_size: usize) -> usize {
+        1 + self.waves as usize + colony_size / COLONY_PER_RAIDER
+    }
+
+    /// A free tile on the edge of the map, or a ring around the town centre
+    /// when the world has no edges
+    fn edge_tile(map: &WorldMap, network: &RoadNetwork, town_centre: IVec2) -> Option<IVec2> {
+        let mut rng = rand::thread_rng();
+        let (cols, rows) = (map.cols as i32, map.rows as i32);
+
+        (0..32)
+            .map(|_| match map.mode {
+                WorldMode::Fixed => match rng.gen_range(0..4) {
+                    0 => IVec2::new(rng.gen_range(0..cols), 0),
+                    1 => IVec2::new(rng.gen_range(0..cols), rows - 1),
+                    2 => IVec2::new(0, rng.gen_range(0..rows)),
+                    _ => IVec2::new(cols - 1, rng.gen_range(0..rows)),
+                },
+                WorldMode::Streaming => {
+                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                    let offset = Vec2::new(angle.cos(), angle.sin()) * STREAMING_RAID_DISTANCE;
+                    town_centre + offset.round().as_ivec2()
+                }
+            })
+            .find(|tile| Walker::can_walk(map, network, *tile))
+    }
+
+    /// Counts down to the next wave while the town centre stands and sends it
+    /// in from the edge of the map
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub(super) fn spawn_waves(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut raids: ResMut<Raids>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        textures: Res<PersonAssets>,
+        town_centre_query: Query<&TilePosition, With<TownCentre>>,
+        unit_query: Query<(), Or<(With<Worker>, With<Soldier>)>>,
+        building_query: Query<(), (With<Building>, Without<Road>)>,
+        mut started: EventWriter<RaidStarted>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        let (Some(map), Ok(town_centre)) = (map, town_centre_query.get_single()) else {
+            return;
+        };
+        if !raids.next.tick(time.delta()).finished() {
+            return;
+        }
+
+        let colony_size = unit_query.iter().count() + building_query.iter().count();
+        let tiles = (0..raids.wave_size(colony_size))
+            .filter_map(|_| Raids::edge_tile(&map, &network, town_centre.0))
+            .collect::<Vec<_>>();
+        for tile in tiles.iter() {
+            Raider::spawn(&mut commands, &textures, &map, *tile);
+        }
+        let raiders = tiles.len();
+
+        raids.waves += 1;
+        raids.in_progress = raids.in_progress || raiders > 0;
+        raids.next = Timer::from_seconds(RAID_SECONDS, TimerMode::Once);
+        started.send(RaidStarted {
+            wave: raids.waves,
+            raiders,
+        });
+        if let Some(tile) = tiles.first() {
+            notifications.send(
+                Notification::new(
+                    Severity::Danger,
+                    format!("Raid! {} raiders are coming for the town centre", raiders),
+                )
+                .at(*tile),
+            );
+        }
+    }
+
+    /// A wave counts as survived once its last raider is gone. Runs before
+    /// new waves spawn so raiders spawned this frame are already counted.
+    pub(super) fn track_survived(
+        mut raids: ResMut<Raids>,
+        raider_query: Query<(), With<Raider>>,
+        mut notifications: EventWriter<Notification>,
+    ) {
+        if raids.in_progress && raider_query.is_empty() {
+            raids.in_progress = false;
+            raids.survived += 1;
+            notifications.send(Notification::new(
+                Severity::Good,
+                format!("Raid {} fought off", raids.waves),
+            ));
+        }
+    }
+}
+
+impl Raider {
+    pub fn spawn(
+        commands: &mut Commands,
+        textures: &PersonAssets,
+        map: &WorldMap,
+        tile: IVec2,
+    ) -> Entity {
+        let position = map.tile_to_world(tile);
+        let textures = [&textures.person, &textures.person_1, &textures.person_2];
+
+        commands
+            .spawn((
+                Raider {
+                    retarget: Timer::from_seconds(RETARGET_SECONDS, TimerMode::Repeating),
+                    holding: false,
+                },
+                Attacker::new(RAIDER_DAMAGE),
+                Health::new(RAIDER_HEALTH),
+                Walker::default(),
+                TilePosition(tile),
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: RAIDER_TINT,
+                        ..default()
+                    },
+                    texture: (*textures.choose(&mut rand::thread_rng()).unwrap()).clone(),
+                    transform: Transform::from_xyz(position.x, position.y, 2.0),
+                    ..default()
+                },
+                Name::new("Raider"),
+            ))
+            .id()
+    }
+
+    /// Raiders go for the closest building or colonist in sight, or else the
+    /// town centre, and stop to fight once they are next to it. They hold
+    /// their ground while they can't walk up to it.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn hunt(
+        time: Res<Time>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        mut raider_query: Query<(&mut Raider, &mut Attacker, &mut Walker, &TilePosition)>,
+        target_query: Query<
+            (
+                Entity,
+                &TilePosition,
+                Option<&BuildingType>,
+                Option<&Facing>,
+            ),
+            (With<Health>, Without<Raider>, Without<Road>),
+        >,
+        town_centre_query: Query<Entity, With<TownCentre>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (mut raider, mut attacker, mut walker, tile) in raider_query.iter_mut() {
+            let lost_target = !attacker
+                .target
+                .is_some_and(|target| target_query.contains(target));
+            let retarget = raider.retarget.tick(time.delta()).just_finished() || lost_target;
+
+            if retarget {
+                let in_sight = target_query
+                    .iter()
+                    .map(|(entity, target_tile, building, facing)| {
+                        let distance = Attacker::target_tiles(target_tile.0, building, facing)
+                            .iter()
+                            .map(|target_tile| (*target_tile - tile.0).abs().max_element())
+                            .min()
+                            .unwrap_or(i32::MAX);
+                        (entity, distance)
+                    })
+                    .filter(|(_, distance)| *distance <= SIGHT)
+                    .min_by_key(|(_, distance)| *distance)
+                    .map(|(entity, _)| entity);
+                let target = in_sight.or_else(|| town_centre_query.get_single().ok());
+
+                if attacker.target != target {
+                    attacker.target = target;
+                    walker.path.clear();
+                }
+            }
+
+            let Some((_, target_tile, building, facing)) = attacker
+                .target
+                .and_then(|target| target_query.get(target).ok())
+            else {
+                continue;
+            };
+
+            let tiles = Attacker::target_tiles(target_tile.0, building, facing);
+            if Attacker::in_reach(tile.0, &tiles) {
+                walker.path.clear();
+                continue;
+            }
+
+            if (walker.is_idle() && !raider.holding) || retarget {
+                let mut goals = tiles;
+                goals.sort_by_key(|goal| (*goal - tile.0).abs().max_element());
+
+                let path = goals
+                    .into_iter()
+                    .find_map(|goal| Walker::find_path(&map, &network, tile.0, goal));
+                raider.holding = path.is_none();
+                walker.path = path.unwrap_or_default();
+            }
+        }
+    }
+}
+
+impl Soldier {
+    /// Soldiers go after raiders that come within sight of their post, and
+    /// go back to patrolling once there are none left. They stay put while
+    /// they can't walk up to the raider.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn defend(
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        building_query: Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+        mut soldier_query: Query<
+            (&Soldier, &mut Attacker, &mut Walker, &TilePosition),
+            Without<Raider>,
+        >,
+        raider_query: Query<(Entity, &TilePosition), With<Raider>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for (soldier, mut attacker, mut walker, tile) in soldier_query.iter_mut() {
+            let post = soldier.post(&building_query).unwrap_or(tile.0);
+            let reach = soldier.patrol_radius + SIGHT;
+
+            let raider = raider_query
+                .iter()
+                .filter(|(_, raider_tile)| (raider_tile.0 - post).abs().max_element() <= reach)
+                .min_by_key(|(_, raider_tile)| (raider_tile.0 - tile.0).abs().max_element())
+                .map(|(entity, raider_tile)| (entity, raider_tile.0));
+
+            if attacker.target != raider.map(|(entity, _)| entity) {
+                attacker.target = raider.map(|(entity, _)| entity);
+                walker.path.clear();
+            }
+
+            let Some((_, raider_tile)) = raider else {
+                continue;
+            };
+
+            if Attacker::in_reach(tile.0, &[raider_tile]) {
+                walker.path.clear();
+            } else if walker.path.back() != Some(&raider_tile) {
+                walker.path =
+                    Walker::find_path(&map, &network, tile.0, raider_tile).unwrap_or_default();
+            }
+        }
+    }
+}
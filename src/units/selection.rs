@@ -0,0 +1,180 @@
+//! Picking units with the mouse and giving them orders. Left click selects the
+//! worker or soldier under the cursor and Shift adds it to the selection,
+//! right click sends the selection there or has it guard the building under
+//! the cursor. T over a barracks trains a soldier.
+
+use bevy::prelude::*;
+
+use super::{CommandUnits, Soldier, TrainSoldier, UnitOrder, Worker};
+use crate::{
+    building::{Building, BuildingState, BuildingType, Facing},
+    loading::TextureAssets,
+    mouse_position::MousePosition,
+    tiles::TilePosition,
+    world_gen::WorldMap,
+    GameState,
+};
+
+/// How close to a unit a click has to land, in tiles
+const PICK_RADIUS: f32 = 0.6;
+
+#[derive(Component)]
+pub struct Selected;
+
+/// Selector drawn under a selected unit
+#[derive(Component)]
+struct SelectionMarker;
+
+/// The mouse driven side of units, the orders themselves are carried out by
+/// `UnitsPlugin`
+pub struct UnitSelectionPlugin;
+
+impl Plugin for UnitSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(UnitSelectionPlugin::select)
+                .with_system(UnitSelectionPlugin::command)
+                .with_system(UnitSelectionPlugin::train_at_barracks)
+                .with_system(UnitSelectionPlugin::show_markers),
+        );
+    }
+}
+
+impl UnitSelectionPlugin {
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn select(
+        mut commands: Commands,
+        buttons: Res<Input<MouseButton>>,
+        keys: Res<Input<KeyCode>>,
+        state: Res<BuildingState>,
+        mouse: Res<MousePosition>,
+        map: Option<Res<WorldMap>>,
+        unit_query: Query<(Entity, &Transform), Or<(With<Worker>, With<Soldier>)>>,
+        selected_query: Query<Entity, With<Selected>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        if state.mode_active || !buttons.just_pressed(MouseButton::Left) {
+            return;
+        }
+
+        let clicked = unit_query
+            .iter()
+            .map(|(entity, transform)| {
+                (
+                    entity,
+                    transform.translation.truncate().distance(mouse.world),
+                )
+            })
+            .filter(|(_, distance)| *distance <= PICK_RADIUS * map.block_size)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity);
+
+        if !keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+            for entity in selected_query.iter() {
+                if Some(entity) != clicked {
+                    commands.entity(entity).remove::<Selected>();
+                }
+            }
+        }
+
+        if let Some(entity) = clicked {
+            commands.entity(entity).insert(Selected);
+        }
+    }
+
+    fn command(
+        buttons: Res<Input<MouseButton>>,
+        state: Res<BuildingState>,
+        mouse: Res<MousePosition>,
+        map: Option<Res<WorldMap>>,
+        selected_query: Query<Entity, With<Selected>>,
+        building_query: Query<(Entity, &BuildingType, &TilePosition, &Facing), With<Building>>,
+        mut event: EventWriter<CommandUnits>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        if state.mode_active || !buttons.just_pressed(MouseButton::Right) {
+            return;
+        }
+
+        let units = selected_query.iter().collect::<Vec<_>>();
+        if units.is_empty() {
+            return;
+        }
+
+        let tile = map.world_to_tile(mouse.world);
+        let guarded = building_query
+            .iter()
+            .find(|(_, building, position, facing)| {
+                **building != BuildingType::Road
+                    && building.tiles(position.0, **facing).contains(&tile)
+            })
+            .map(|(entity, ..)| entity);
+
+        let order = match guarded {
+            Some(building) => UnitOrder::Guard(building),
+            None => UnitOrder::MoveTo(tile),
+        };
+        event.send(CommandUnits { units, order });
+    }
+
+    fn train_at_barracks(
+        keys: Res<Input<KeyCode>>,
+        state: Res<BuildingState>,
+        mouse: Res<MousePosition>,
+        map: Option<Res<WorldMap>>,
+        mut event: EventWriter<TrainSoldier>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        if !state.mode_active && keys.just_pressed(KeyCode::T) {
+            event.send(TrainSoldier {
+                tile: map.world_to_tile(mouse.world),
+            });
+        }
+    }
+
+    fn show_markers(
+        mut commands: Commands,
+        textures: Res<TextureAssets>,
+        map: Option<Res<WorldMap>>,
+        added_query: Query<Entity, Added<Selected>>,
+        removed: RemovedComponents<Selected>,
+        marker_query: Query<(Entity, &Parent), With<SelectionMarker>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for entity in added_query.iter() {
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((
+                    SelectionMarker,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(map.block_size)),
+                            ..default()
+                        },
+                        texture: textures.texture_selector.clone(),
+                        transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                        ..default()
+                    },
+                ));
+            });
+        }
+
+        for entity in removed.iter() {
+            for (marker, parent) in marker_query.iter() {
+                if parent.get() == entity {
+                    commands.entity(marker).despawn_recursive();
+                }
+            }
+        }
+    }
+}
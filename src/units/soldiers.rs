@@ -0,0 +1,229 @@
+//! Soldiers, trained from workers at the barracks. Every soldier patrols
+//! around a post, either a tile it was sent to or a building it guards.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{Attacker, Health, Walker, Worker};
+use crate::{
+    building::{Blueprint, Building, BuildingType, Facing, RoadNetwork},
+    economy::{Good, Goods, Stockpile},
+    tiles::TilePosition,
+    world_gen::WorldMap,
+};
+
+/// What the barracks charges to train one soldier
+pub const SOLDIER_COST: &Goods = &[(Good::Food, 20), (Good::Coin, 10)];
+const SOLDIER_HEALTH: u32 = 100;
+const SOLDIER_ATTACK: u32 = 10;
+const PATROL_RADIUS: i32 = 3;
+/// Seconds a soldier waits between patrol walks
+const PATROL_SECONDS: f32 = 4.0;
+/// Tint that tells soldiers apart from workers
+const SOLDIER_TINT: Color = Color::rgb(1.0, 0.55, 0.55);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnitOrder {
+    /// Walk to a tile and patrol around it
+    MoveTo(IVec2),
+    /// Patrol around the entrance of a building for as long as it stands
+    Guard(Entity),
+}
+
+#[derive(Component)]
+pub struct Soldier {
+    /// How far from its post the soldier patrols
+    pub patrol_radius: i32,
+    pub order: UnitOrder,
+    patrol: Timer,
+}
+
+/// Asks the barracks on `tile` to train the closest free worker that can walk
+/// to it into a soldier, ignored when there is none or the stockpile can't pay
+pub struct TrainSoldier {
+    pub tile: IVec2,
+}
+
+/// Gives `order` to every unit in `units`, workers can only be moved
+pub struct CommandUnits {
+    pub units: Vec<Entity>,
+    pub order: UnitOrder,
+}
+
+impl Soldier {
+    pub fn new(order: UnitOrder) -> Self {
+        Self {
+            patrol_radius: PATROL_RADIUS,
+            order,
+            patrol: Timer::from_seconds(PATROL_SECONDS, TimerMode::Repeating),
+        }
+    }
+
+    /// The tile the soldier patrols around, none once the building it guards
+    /// is gone
+    pub(super) fn post(
+        &self,
+        building_query: &Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+    ) -> Option<IVec2> {
+        match self.order {
+            UnitOrder::MoveTo(tile) => Some(tile),
+            UnitOrder::Guard(entity) => building_query
+                .get(entity)
+                .ok()
+                .map(|(building, tile, facing)| building.entrance(tile.0, *facing)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub(super) fn train(
+        mut commands: Commands,
+        mut events: EventReader<TrainSoldier>,
+        mut stockpile: ResMut<Stockpile>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        barracks_query: Query<
+            (Entity, &BuildingType, &TilePosition, &Facing),
+            (With<Building>, Without<Blueprint>),
+        >,
+        mut worker_query: Query<(Entity, &Worker, &TilePosition, &mut Walker, &mut Sprite)>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        let mut trained = vec![];
+
+        for TrainSoldier { tile } in events.iter() {
+            let Some((barracks, entrance)) = barracks_query
+                .iter()
+                .find(|(_, building, position, facing)| {
+                    **building == BuildingType::Barracks
+                        && building.tiles(position.0, **facing).contains(tile)
+                })
+                .map(|(entity, building, position, facing)| {
+                    (entity, building.entrance(position.0, *facing))
+                })
+            else {
+                continue;
+            };
+
+            // The closest idle worker that can walk to the barracks
+            let mut idle = worker_query
+                .iter()
+                .filter(|(entity, worker, ..)| {
+                    worker.workplace.is_none() && !trained.contains(entity)
+                })
+                .map(|(entity, _, position, ..)| (entity, position.0))
+                .collect::<Vec<_>>();
+            idle.sort_by_key(|(_, position)| {
+                let offset = (*position - entrance).abs();
+                offset.x + offset.y
+            });
+            let Some((recruit, path)) = idle.into_iter().find_map(|(entity, position)| {
+                Walker::find_path(&map, &network, position, entrance).map(|path| (entity, path))
+            }) else {
+                continue;
+            };
+
+            if !stockpile.spend(SOLDIER_COST) {
+                continue;
+            }
+
+            let (_, _, _, mut walker, mut sprite) = worker_query.get_mut(recruit).unwrap();
+            walker.path = path;
+            sprite.color = SOLDIER_TINT;
+
+            commands.entity(recruit).remove::<Worker>().insert((
+                Soldier::new(UnitOrder::Guard(barracks)),
+                Attacker::new(SOLDIER_ATTACK),
+                Health::new(SOLDIER_HEALTH),
+                Name::new("Soldier"),
+            ));
+            trained.push(recruit);
+        }
+    }
+
+    /// Sends soldiers to their new post, and workers wherever they are moved
+    #[allow(clippy::type_complexity)]
+    pub(super) fn follow_orders(
+        mut events: EventReader<CommandUnits>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        building_query: Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+        mut soldier_query: Query<(&mut Soldier, &mut Walker, &TilePosition), Without<Worker>>,
+        mut worker_query: Query<(&mut Worker, &mut Walker, &TilePosition), Without<Soldier>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for CommandUnits { units, order } in events.iter() {
+            for unit in units {
+                if let Ok((mut soldier, mut walker, tile)) = soldier_query.get_mut(*unit) {
+                    soldier.order = *order;
+
+                    if let Some(post) = soldier.post(&building_query) {
+                        walker.path =
+                            Walker::find_path(&map, &network, tile.0, post).unwrap_or_default();
+                    }
+                } else if let Ok((mut worker, mut walker, tile)) = worker_query.get_mut(*unit) {
+                    let UnitOrder::MoveTo(target) = order else {
+                        continue;
+                    };
+
+                    worker.workplace = None;
+                    walker.path =
+                        Walker::find_path(&map, &network, tile.0, *target).unwrap_or_default();
+                }
+            }
+        }
+    }
+
+    /// Idle soldiers that aren't fighting head back to their post when they
+    /// are too far from it and otherwise walk to a random spot within their
+    /// patrol radius
+    pub(super) fn patrol(
+        time: Res<Time>,
+        map: Option<Res<WorldMap>>,
+        network: Res<RoadNetwork>,
+        building_query: Query<(&BuildingType, &TilePosition, &Facing), With<Building>>,
+        mut soldier_query: Query<(&mut Soldier, &Attacker, &mut Walker, &TilePosition)>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        let mut rng = rand::thread_rng();
+
+        for (mut soldier, attacker, mut walker, tile) in soldier_query.iter_mut() {
+            if attacker.target.is_some()
+                || !walker.is_idle()
+                || !soldier.patrol.tick(time.delta()).just_finished()
+            {
+                continue;
+            }
+
+            let Some(post) = soldier.post(&building_query) else {
+                // The building it guarded is gone, it holds its ground instead
+                soldier.order = UnitOrder::MoveTo(tile.0);
+                continue;
+            };
+
+            let radius = soldier.patrol_radius;
+            let offset = (tile.0 - post).abs();
+            let target = match offset.x.max(offset.y) > radius {
+                true => post,
+                false => {
+                    post + IVec2::new(
+                        rng.gen_range(-radius..=radius),
+                        rng.gen_range(-radius..=radius),
+                    )
+                }
+            };
+
+            if target == post || Walker::can_walk(&map, &network, target) {
+                if let Some(path) = Walker::find_path(&map, &network, tile.0, target) {
+                    walker.path = path;
+                }
+            }
+        }
+    }
+}
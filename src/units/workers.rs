@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{Health, Walker};
+use crate::{
+    building::{RoadNetwork, Territory, TownCentre},
+    loading::PersonAssets,
+    tiles::TilePosition,
+    world_gen::WorldMap,
+};
+
+/// Workers that move in when the town centre is built
+const STARTING_WORKERS: usize = 3;
+/// How far an idle worker wanders from where it stands
+const WANDER_RADIUS: i32 = 4;
+/// Seconds an idle worker waits before wandering off again
+const IDLE_SECONDS: f32 = 3.0;
+const WORKER_HEALTH: u32 = 40;
+
+#[derive(Component)]
+pub struct Worker {
+    idle: Timer,
+    /// The building the worker is staffing, it stops wandering while it has one
+    pub workplace: Option<Entity>,
+}
+
+impl Worker {
+    pub fn spawn(
+        commands: &mut Commands,
+        textures: &PersonAssets,
+        map: &WorldMap,
+        tile: IVec2,
+    ) -> Entity {
+        let position = map.tile_to_world(tile);
+        let textures = [
+            &textures.person,
+            &textures.person_1,
+            &textures.person_2,
+            &textures.person_3,
+            &textures.person_4,
+            &textures.person_5,
+            &textures.person_6,
+            &textures.person_7,
+        ];
+
+        commands
+            .spawn((
+                Worker {
+                    idle: Timer::from_seconds(IDLE_SECONDS, TimerMode::Repeating),
+                    workplace: None,
+                },
+                Health::new(WORKER_HEALTH),
+                Walker::default(),
+                TilePosition(tile),
+                SpriteBundle {
+                    texture: (*textures.choose(&mut rand::thread_rng()).unwrap()).clone(),
+                    transform: Transform::from_xyz(position.x, position.y, 2.0),
+                    ..default()
+                },
+                Name::new("Worker"),
+            ))
+            .id()
+    }
+
+    pub(super) fn arrive_with_town_centre(
+        mut commands: Commands,
+        textures: Res<PersonAssets>,
+        map: Option<Res<WorldMap>>,
+        town_centre_query: Query<&TilePosition, Added<TownCentre>>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+
+        for tile in town_centre_query.iter() {
+            for _ in 0..STARTING_WORKERS {
+                Worker::spawn(&mut commands, &textures, &map, tile.0);
+            }
+        }
+    }
+
+    /// Idle workers without a workplace stroll to a random spot inside the
+    /// territory
+    pub(super) fn wander(
+        time: Res<Time>,
+        map: Option<Res<WorldMap>>,
+        territory: Res<Territory>,
+        network: Res<RoadNetwork>,
+        mut worker_query: Query<(&mut Worker, &mut Walker, &TilePosition)>,
+    ) {
+        let Some(map) = map else {
+            return;
+        };
+        let mut rng = rand::thread_rng();
+
+        for (mut worker, mut walker, tile) in worker_query.iter_mut() {
+            if worker.workplace.is_some()
+                || !walker.is_idle()
+                || !worker.idle.tick(time.delta()).just_finished()
+            {
+                continue;
+            }
+
+            let target = tile.0
+                + IVec2::new(
+                    rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS),
+                    rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS),
+                );
+            let path = Walker::straight_path(tile.0, target);
+
+            let walkable = path
+                .iter()
+                .all(|step| territory.contains(*step) && Walker::can_walk(&map, &network, *step));
+
+            if walkable {
+                walker.path = path;
+            }
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    map::{WorldMap, CHUNK_SIZE},
+    transitions::Transition,
+};
+use crate::{
+    bounds::Bounds2, building::Buildable, global_state::WorldMode, loading::TextureAssets, tiles::*,
+};
+
+/// Chunks this far outside of the camera view still keep their tile entities
+const ACTIVE_CHUNK_MARGIN: i32 = 1;
+/// In an endless world, chunk meshes further than this outside of the camera
+/// view are despawned. Their tiles stay in the `WorldMap`.
+const UNLOAD_CHUNK_MARGIN: i32 = 4;
+
+/// Every terrain and resource texture packed into one image, so that a whole
+/// chunk can be drawn as a single mesh.
+#[derive(Resource)]
+pub struct TerrainAtlas {
+    atlas: TextureAtlas,
+    material: Handle<ColorMaterial>,
+    /// Faded edge and corner overlays for terrain blending over its neighbours
+    transitions: HashMap<(TileType, Transition), Handle<Image>>,
+}
+
+impl TerrainAtlas {
+    fn textures(textures: &TextureAssets) -> Vec<Handle<Image>> {
+        vec![
+            textures.texture_dirt.clone(),
+            textures.texture_grass.clone(),
+            textures.texture_sand.clone(),
+            textures.texture_stone.clone(),
+            textures.texture_water.clone(),
+            textures.texture_tree_1.clone(),
+            textures.texture_tree_2.clone(),
+            textures.texture_tree_3.clone(),
+            textures.texture_tree_4.clone(),
+            textures.texture_stone_1.clone(),
+            textures.texture_stone_2.clone(),
+            textures.texture_stone_3.clone(),
+            textures.texture_berry_1.clone(),
+            textures.texture_berry_2.clone(),
+        ]
+    }
+
+    /// UV coordinates of `texture` within the atlas, inset by half a pixel so
+    /// neighbouring textures don't bleed into each other
+    fn uv_rect(&self, texture: &Handle<Image>) -> Rect {
+        let index = self.atlas.get_texture_index(texture).unwrap_or(0);
+        let rect = self.atlas.textures[index];
+        let inset = Vec2::splat(0.5);
+
+        Rect {
+            min: (rect.min + inset) / self.atlas.size,
+            max: (rect.max - inset) / self.atlas.size,
+        }
+    }
+}
+
+/// Entities spawned for each chunk: a mesh for every rendered chunk, and tile
+/// entities for the chunks close enough to the camera to be interacted with.
+#[derive(Resource, Default)]
+pub struct ChunkEntities {
+    meshes: HashMap<IVec2, Entity>,
+    active: HashMap<IVec2, Vec<Entity>>,
+}
+
+#[derive(Component)]
+pub struct ChunkMesh(pub IVec2);
+
+pub(super) struct ChunkRenderer;
+
+impl ChunkRenderer {
+    pub(super) fn build_atlas(
+        mut commands: Commands,
+        textures: Res<TextureAssets>,
+        mut images: ResMut<Assets<Image>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let mut builder = TextureAtlasBuilder::default();
+        let mut transitions = HashMap::new();
+
+        for tile_type in [
+            TileType::Sand,
+            TileType::Dirt,
+            TileType::Grass,
+            TileType::Stone,
+        ] {
+            for transition in Transition::ALL {
+                let overlay = images
+                    .get(&tile_type.texture(&textures))
+                    .and_then(|image| transition.overlay(image));
+
+                if let Some(overlay) = overlay {
+                    transitions.insert((tile_type, transition), images.add(overlay));
+                }
+            }
+        }
+
+        let handles = TerrainAtlas::textures(&textures)
+            .into_iter()
+            .chain(transitions.values().cloned());
+
+        for handle in handles {
+            if let Some(image) = images.get(&handle) {
+                builder.add_texture(handle.clone(), image);
+            }
+        }
+
+        let atlas = builder
+            .finish(&mut images)
+            .expect("terrain textures should fit in a single atlas");
+        let material = materials.add(ColorMaterial::from(atlas.texture.clone()));
+
+        commands.insert_resource(TerrainAtlas {
+            atlas,
+            material,
+            transitions,
+        });
+    }
+
+    /// (Re)builds the mesh of every chunk that changed since the last frame
+    pub(super) fn rebuild_dirty_chunks(
+        mut commands: Commands,
+        map: Option<ResMut<WorldMap>>,
+        atlas: Option<Res<TerrainAtlas>>,
+        textures: Res<TextureAssets>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut chunk_entities: ResMut<ChunkEntities>,
+    ) {
+        let (Some(mut map), Some(atlas)) = (map, atlas) else {
+            return;
+        };
+
+        for chunk in map.take_dirty_chunks() {
+            let mesh =
+                Mesh2dHandle(meshes.add(ChunkRenderer::build_mesh(&map, chunk, &atlas, &textures)));
+
+            match chunk_entities.meshes.get(&chunk) {
+                Some(entity) => {
+                    commands.entity(*entity).insert(mesh);
+                }
+                None => {
+                    let origin = map.tile_to_world(chunk * CHUNK_SIZE);
+                    let entity = commands
+                        .spawn((
+                            ChunkMesh(chunk),
+                            MaterialMesh2dBundle {
+                                mesh,
+                                material: atlas.material.clone(),
+                                transform: Transform::from_xyz(origin.x, origin.y, 0.0),
+                                ..default()
+                            },
+                            Name::new("Chunk"),
+                        ))
+                        .id();
+
+                    chunk_entities.meshes.insert(chunk, entity);
+                }
+            }
+        }
+    }
+
+    fn build_mesh(
+        map: &WorldMap,
+        chunk: IVec2,
+        atlas: &TerrainAtlas,
+        textures: &TextureAssets,
+    ) -> Mesh {
+        let origin = map.tile_to_world(chunk * CHUNK_SIZE);
+        let half = map.block_size / 2.;
+
+        let mut positions: Vec<[f32; 3]> = vec![];
+        let mut uvs: Vec<[f32; 2]> = vec![];
+        let mut indices: Vec<u32> = vec![];
+        let mut quads: Vec<(Vec2, Rect)> = vec![];
+        let mut overlays: Vec<(Vec2, Rect)> = vec![];
+
+        for tile in WorldMap::chunk_tiles(chunk) {
+            let Some(map_tile) = map.get(tile) else {
+                continue;
+            };
+
+            let centre = map.tile_to_world(tile) - origin;
+            quads.push((centre, atlas.uv_rect(&map_tile.texture(textures))));
+
+            for key in Transition::for_tile(map, tile) {
+                if let Some(handle) = atlas.transitions.get(&key) {
+                    overlays.push((centre, atlas.uv_rect(handle)));
+                }
+            }
+        }
+
+        // Meshes are drawn in index order, so overlays go last to sit on top
+        for (centre, uv) in quads.into_iter().chain(overlays) {
+            let first = positions.len() as u32;
+
+            positions.extend([
+                [centre.x - half, centre.y - half, 0.0],
+                [centre.x + half, centre.y - half, 0.0],
+                [centre.x + half, centre.y + half, 0.0],
+                [centre.x - half, centre.y + half, 0.0],
+            ]);
+            uvs.extend([
+                [uv.min.x, uv.max.y],
+                [uv.max.x, uv.max.y],
+                [uv.max.x, uv.min.y],
+                [uv.min.x, uv.min.y],
+            ]);
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+
+        let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+
+    /// The lowest and highest chunk coordinates seen by the camera, grown by `margin` chunks
+    pub(super) fn chunks_in_view(
+        map: &WorldMap,
+        camera: &Transform,
+        projection: &OrthographicProjection,
+        margin: i32,
+    ) -> (IVec2, IVec2) {
+        let half_view = Vec2::new(
+            projection.right - projection.left,
+            projection.top - projection.bottom,
+        ) * projection.scale
+            / 2.;
+        let centre = camera.translation.truncate();
+
+        (
+            WorldMap::chunk_of(map.world_to_tile(centre - half_view)) - margin,
+            WorldMap::chunk_of(map.world_to_tile(centre + half_view)) + margin,
+        )
+    }
+
+    /// Spawns tile entities for the chunks around the camera and despawns them
+    /// for the chunks that left it. Chunk meshes outside of the view are hidden,
+    /// or in an endless world unloaded once far enough away.
+    pub(super) fn update_active_chunks(
+        mut commands: Commands,
+        map: Option<ResMut<WorldMap>>,
+        camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+        mut mesh_query: Query<(&ChunkMesh, &mut Visibility)>,
+        mut chunk_entities: ResMut<ChunkEntities>,
+    ) {
+        let (Some(mut map), Ok((camera, projection))) = (map, camera_query.get_single()) else {
+            return;
+        };
+
+        let (min, max) =
+            ChunkRenderer::chunks_in_view(&map, camera, projection, ACTIVE_CHUNK_MARGIN);
+
+        let in_view = |chunk: &IVec2| {
+            chunk.x >= min.x && chunk.y >= min.y && chunk.x <= max.x && chunk.y <= max.y
+        };
+
+        match map.mode {
+            WorldMode::Fixed => {
+                for (chunk, mut visibility) in mesh_query.iter_mut() {
+                    let visible = in_view(&chunk.0);
+                    if visibility.is_visible != visible {
+                        visibility.is_visible = visible;
+                    }
+                }
+            }
+            WorldMode::Streaming => {
+                let (keep_min, keep_max) =
+                    ChunkRenderer::chunks_in_view(&map, camera, projection, UNLOAD_CHUNK_MARGIN);
+
+                let far = chunk_entities
+                    .meshes
+                    .keys()
+                    .filter(|chunk| {
+                        chunk.x < keep_min.x
+                            || chunk.y < keep_min.y
+                            || chunk.x > keep_max.x
+                            || chunk.y > keep_max.y
+                    })
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                for chunk in far {
+                    if let Some(entity) = chunk_entities.meshes.remove(&chunk) {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+
+                for x in min.x..=max.x {
+                    for y in min.y..=max.y {
+                        let chunk = IVec2::new(x, y);
+                        if !chunk_entities.meshes.contains_key(&chunk) {
+                            map.mark_dirty(chunk);
+                        }
+                    }
+                }
+            }
+        }
+
+        let wanted = map
+            .chunk_coords()
+            .filter(|chunk| in_view(chunk))
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let stale = chunk_entities
+            .active
+            .keys()
+            .filter(|chunk| !wanted.contains(chunk))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for chunk in stale {
+            for entity in chunk_entities.active.remove(&chunk).unwrap_or_default() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        for chunk in wanted {
+            if chunk_entities.active.contains_key(&chunk) {
+                continue;
+            }
+
+            let entities = ChunkRenderer::spawn_chunk_tiles(&mut commands, &map, chunk);
+            chunk_entities.active.insert(chunk, entities);
+        }
+    }
+
+    fn spawn_chunk_tiles(commands: &mut Commands, map: &WorldMap, chunk: IVec2) -> Vec<Entity> {
+        let mut entities = vec![];
+
+        for tile in WorldMap::chunk_tiles(chunk) {
+            let Some(map_tile) = map.get(tile) else {
+                continue;
+            };
+
+            let mut entity = commands.spawn((
+                Tile(map_tile.tile_type),
+                TilePosition(tile),
+                Bounds2 {
+                    position: map.tile_to_world(tile),
+                    size: Vec2::splat(map.block_size),
+                },
+            ));
+
+            if map_tile.is_buildable() {
+                entity.insert(Buildable);
+            }
+
+            let lvl = map_tile.resource_lvl;
+            match map_tile.resource {
+                Some(ResourceKind::Tree) => {
+                    entity.insert((ResourceTile, ResourceTree { lvl }));
+                }
+                Some(ResourceKind::Stone) => {
+                    entity.insert((ResourceTile, ResourceStone { lvl }));
+                }
+                Some(ResourceKind::Berry) => {
+                    entity.insert((ResourceTile, ResourceBerry { lvl }));
+                }
+                None => {}
+            }
+
+            entities.push(entity.id());
+        }
+
+        entities
+    }
+}
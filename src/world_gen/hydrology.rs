@@ -0,0 +1,221 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::WorldGenerator;
+use crate::tiles::TileType;
+
+/// Tiles this much higher than a lake's lowest point can still be flooded by it
+const LAKE_DEPTH: f32 = 0.05;
+const MAX_LAKE_SIZE: usize = 24;
+/// Rivers only start on tiles at or above this elevation
+const RIVER_SOURCE_ELEVATION: f32 = 0.6;
+/// Map area (in tiles) per lake / river
+const TILES_PER_LAKE: usize = 512;
+const TILES_PER_RIVER: usize = 1024;
+
+const CARDINALS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const SURROUNDING: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl WorldGenerator {
+    /// Connects the water of the perlin atlas into lakes and rivers.
+    ///
+    /// `elevation` and `tiles` are both indexed as `[x][y]`. Lakes are placed in
+    /// elevation minima, rivers flow downhill from high ground until they hit the
+    /// map edge or other water, and any grass or dirt touching water becomes sand.
+    pub(super) fn apply_hydrology(
+        elevation: &[Vec<f32>],
+        tiles: &mut [Vec<TileType>],
+        rng: &mut impl Rng,
+    ) {
+        let cols = tiles.len();
+        let rows = tiles.first().map_or(0, |column| column.len());
+        if cols < 3 || rows < 3 {
+            return;
+        }
+
+        WorldGenerator::place_lakes(elevation, tiles, (cols * rows) / TILES_PER_LAKE, rng);
+        WorldGenerator::trace_rivers(elevation, tiles, (cols * rows) / TILES_PER_RIVER, rng);
+        WorldGenerator::form_banks(tiles);
+    }
+
+    fn place_lakes(
+        elevation: &[Vec<f32>],
+        tiles: &mut [Vec<TileType>],
+        count: usize,
+        rng: &mut impl Rng,
+    ) {
+        let mut minima: Vec<(usize, usize)> = vec![];
+
+        for x in 1..tiles.len() - 1 {
+            for y in 1..tiles[x].len() - 1 {
+                if tiles[x][y] == TileType::Water {
+                    continue;
+                }
+
+                let is_minimum = SURROUNDING.iter().all(|(dx, dy)| {
+                    let (nx, ny) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+                    elevation[x][y] < elevation[nx][ny]
+                });
+
+                if is_minimum {
+                    minima.push((x, y));
+                }
+            }
+        }
+
+        minima.shuffle(rng);
+
+        for (x, y) in minima.into_iter().take(count) {
+            WorldGenerator::flood_lake(elevation, tiles, (x, y));
+        }
+    }
+
+    /// Floods outward from `origin` over every connected tile that sits within
+    /// `LAKE_DEPTH` of the origin's elevation.
+    fn flood_lake(elevation: &[Vec<f32>], tiles: &mut [Vec<TileType>], origin: (usize, usize)) {
+        let surface = elevation[origin.0][origin.1] + LAKE_DEPTH;
+        let mut queue = VecDeque::from([origin]);
+        let mut flooded: HashSet<(usize, usize)> = HashSet::from([origin]);
+
+        while let Some((x, y)) = queue.pop_front() {
+            tiles[x][y] = TileType::Water;
+
+            if flooded.len() >= MAX_LAKE_SIZE {
+                continue;
+            }
+
+            for neighbour in WorldGenerator::neighbours(tiles, (x, y), &CARDINALS) {
+                if flooded.len() >= MAX_LAKE_SIZE {
+                    break;
+                }
+
+                if elevation[neighbour.0][neighbour.1] <= surface && flooded.insert(neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    fn trace_rivers(
+        elevation: &[Vec<f32>],
+        tiles: &mut [Vec<TileType>],
+        count: usize,
+        rng: &mut impl Rng,
+    ) {
+        let mut sources: Vec<(usize, usize)> = vec![];
+
+        for (x, column) in tiles.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                if *tile != TileType::Water && elevation[x][y] >= RIVER_SOURCE_ELEVATION {
+                    sources.push((x, y));
+                }
+            }
+        }
+
+        sources.shuffle(rng);
+
+        for source in sources.into_iter().take(count) {
+            WorldGenerator::trace_river(elevation, tiles, source);
+        }
+    }
+
+    /// Walks downhill from `source`, always stepping to the lowest unvisited
+    /// neighbour. The river ends at the map edge or when it reaches water; if it
+    /// gets stuck in a pit, a lake is flooded there instead.
+    fn trace_river(elevation: &[Vec<f32>], tiles: &mut [Vec<TileType>], source: (usize, usize)) {
+        let cols = tiles.len();
+        let rows = tiles[0].len();
+        let max_length = cols + rows;
+
+        let mut path: Vec<(usize, usize)> = vec![];
+        let mut visited: HashSet<(usize, usize)> = HashSet::new();
+        let mut current = source;
+
+        loop {
+            path.push(current);
+            visited.insert(current);
+
+            let (x, y) = current;
+            let on_edge = x == 0 || y == 0 || x == cols - 1 || y == rows - 1;
+            if on_edge {
+                break;
+            }
+
+            let neighbours = WorldGenerator::neighbours(tiles, current, &CARDINALS);
+            let reached_water = neighbours
+                .iter()
+                .any(|&(nx, ny)| tiles[nx][ny] == TileType::Water && !visited.contains(&(nx, ny)));
+            if reached_water {
+                break;
+            }
+
+            let next = neighbours
+                .into_iter()
+                .filter(|neighbour| !visited.contains(neighbour))
+                .min_by(|a, b| elevation[a.0][a.1].total_cmp(&elevation[b.0][b.1]));
+
+            match next {
+                Some(next) if path.len() < max_length => current = next,
+                _ => {
+                    WorldGenerator::flood_lake(elevation, tiles, current);
+                    break;
+                }
+            }
+        }
+
+        for (x, y) in path {
+            tiles[x][y] = TileType::Water;
+        }
+    }
+
+    /// Turns grass and dirt that touches water into sand
+    fn form_banks(tiles: &mut [Vec<TileType>]) {
+        let mut banks: Vec<(usize, usize)> = vec![];
+
+        for (x, column) in tiles.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                if !matches!(tile, TileType::Grass | TileType::Dirt) {
+                    continue;
+                }
+
+                let touches_water = WorldGenerator::neighbours(tiles, (x, y), &SURROUNDING)
+                    .iter()
+                    .any(|&(nx, ny)| tiles[nx][ny] == TileType::Water);
+
+                if touches_water {
+                    banks.push((x, y));
+                }
+            }
+        }
+
+        for (x, y) in banks {
+            tiles[x][y] = TileType::Sand;
+        }
+    }
+
+    fn neighbours(
+        tiles: &[Vec<TileType>],
+        (x, y): (usize, usize),
+        offsets: &[(i32, i32)],
+    ) -> Vec<(usize, usize)> {
+        let cols = tiles.len() as i32;
+        let rows = tiles[0].len() as i32;
+
+        offsets
+            .iter()
+            .map(|(dx, dy)| (x as i32 + dx, y as i32 + dy))
+            .filter(|(nx, ny)| *nx >= 0 && *ny >= 0 && *nx < cols && *ny < rows)
+            .map(|(nx, ny)| (nx as usize, ny as usize))
+            .collect()
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{global_state::WorldMode, loading::TextureAssets, tiles::*};
+
+/// Width and height of a chunk in tiles
+pub const CHUNK_SIZE: i32 = 16;
+
+#[derive(Clone, Copy)]
+pub struct MapTile {
+    pub tile_type: TileType,
+    pub resource: Option<ResourceKind>,
+    pub resource_lvl: usize,
+    /// A building has been placed on this tile
+    pub occupied: bool,
+}
+
+impl MapTile {
+    pub fn new(tile_type: TileType) -> Self {
+        Self {
+            tile_type,
+            resource: None,
+            resource_lvl: 0,
+            occupied: false,
+        }
+    }
+
+    /// Nothing is built or growing here. Which terrain a building needs is
+    /// up to its placement rules.
+    pub fn is_buildable(&self) -> bool {
+        !self.occupied && self.resource.is_none()
+    }
+
+    pub fn map_colour(&self) -> Color {
+        self.resource
+            .map_or(self.tile_type.map_colour(), |kind| kind.map_colour())
+    }
+
+    pub fn texture(&self, textures: &TextureAssets) -> Handle<Image> {
+        let lvl = self.resource_lvl;
+
+        match self.resource {
+            Some(ResourceKind::Tree) => ResourceTree { lvl }.texture(textures),
+            Some(ResourceKind::Stone) => ResourceStone { lvl }.texture(textures),
+            Some(ResourceKind::Berry) => ResourceBerry { lvl }.texture(textures),
+            None => self.tile_type.texture(textures),
+        }
+    }
+}
+
+struct Chunk {
+    /// Indexed as `y * CHUNK_SIZE + x` in chunk-local coordinates
+    tiles: Vec<MapTile>,
+}
+
+/// The world, stored as square chunks of tiles keyed by chunk coordinate.
+///
+/// In a fixed world tile coordinates start at `(0, 0)` in the bottom left
+/// corner of the map and the map is centred on the world origin. A streaming
+/// world has no edges, tile `(0, 0)` sits on the world origin and chunks are
+/// added as they are generated.
+#[derive(Resource)]
+pub struct WorldMap {
+    pub mode: WorldMode,
+    pub cols: usize,
+    pub rows: usize,
+    pub block_size: f32,
+    pub start: IVec2,
+    chunks: HashMap<IVec2, Chunk>,
+    /// Chunks whose rendering is out of date
+    dirty_chunks: HashSet<IVec2>,
+    /// Chunks whose rendering is out of date on the minimap
+    minimap_dirty_chunks: HashSet<IVec2>,
+}
+
+impl WorldMap {
+    /// Builds the map from generated tiles, indexed as `tiles[x][y]`
+    pub fn from_columns(tiles: Vec<Vec<MapTile>>, block_size: f32, start: IVec2) -> Self {
+        let mut map = Self {
+            mode: WorldMode::Fixed,
+            cols: tiles.len(),
+            rows: tiles.first().map_or(0, |column| column.len()),
+            block_size,
+            start,
+            chunks: HashMap::new(),
+            dirty_chunks: HashSet::new(),
+            minimap_dirty_chunks: HashSet::new(),
+        };
+
+        map.insert_columns(IVec2::ZERO, tiles);
+        map
+    }
+
+    /// An empty endless map, chunks are added with `insert_columns` and `insert_chunk`
+    pub fn streaming(block_size: f32) -> Self {
+        Self {
+            mode: WorldMode::Streaming,
+            cols: 0,
+            rows: 0,
+            block_size,
+            start: IVec2::ZERO,
+            chunks: HashMap::new(),
+            dirty_chunks: HashSet::new(),
+            minimap_dirty_chunks: HashSet::new(),
+        }
+    }
+
+    /// Writes `tiles`, indexed as `tiles[x][y]`, with `tiles[0][0]` at `origin`
+    pub fn insert_columns(&mut self, origin: IVec2, tiles: Vec<Vec<MapTile>>) {
+        for (x, column) in tiles.into_iter().enumerate() {
+            for (y, tile) in column.into_iter().enumerate() {
+                let coord = origin + IVec2::new(x as i32, y as i32);
+                let chunk = WorldMap::chunk_of(coord);
+
+                self.chunks
+                    .entry(chunk)
+                    .or_insert_with(|| Chunk {
+                        tiles: vec![
+                            MapTile::new(TileType::Water);
+                            (CHUNK_SIZE * CHUNK_SIZE) as usize
+                        ],
+                    })
+                    .tiles[WorldMap::local_index(coord)] = tile;
+                self.mark_surrounding_dirty(chunk);
+            }
+        }
+    }
+
+    /// Adds a whole chunk, `tiles` indexed as `y * CHUNK_SIZE + x`
+    pub fn insert_chunk(&mut self, chunk: IVec2, tiles: Vec<MapTile>) {
+        self.chunks.insert(chunk, Chunk { tiles });
+        self.mark_surrounding_dirty(chunk);
+    }
+
+    pub fn has_chunk(&self, chunk: IVec2) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// The chunk coordinate containing `tile`
+    pub fn chunk_of(tile: IVec2) -> IVec2 {
+        IVec2::new(tile.x.div_euclid(CHUNK_SIZE), tile.y.div_euclid(CHUNK_SIZE))
+    }
+
+    /// Every tile coordinate inside the chunk at `chunk`
+    pub fn chunk_tiles(chunk: IVec2) -> impl Iterator<Item = IVec2> {
+        let origin = chunk * CHUNK_SIZE;
+
+        (0..CHUNK_SIZE).flat_map(move |y| (0..CHUNK_SIZE).map(move |x| origin + IVec2::new(x, y)))
+    }
+
+    fn local_index(tile: IVec2) -> usize {
+        let x = tile.x.rem_euclid(CHUNK_SIZE);
+        let y = tile.y.rem_euclid(CHUNK_SIZE);
+
+        (y * CHUNK_SIZE + x) as usize
+    }
+
+    pub fn in_bounds(&self, tile: IVec2) -> bool {
+        if self.mode == WorldMode::Streaming {
+            return true;
+        }
+
+        tile.x >= 0 && tile.y >= 0 && tile.x < self.cols as i32 && tile.y < self.rows as i32
+    }
+
+    pub fn get(&self, tile: IVec2) -> Option<&MapTile> {
+        if !self.in_bounds(tile) {
+            return None;
+        }
+
+        self.chunks
+            .get(&WorldMap::chunk_of(tile))
+            .map(|chunk| &chunk.tiles[WorldMap::local_index(tile)])
+    }
+
+    /// Changes made through here are not re-rendered
+    pub fn get_mut(&mut self, tile: IVec2) -> Option<&mut MapTile> {
+        if !self.in_bounds(tile) {
+            return None;
+        }
+
+        self.chunks
+            .get_mut(&WorldMap::chunk_of(tile))
+            .map(|chunk| &mut chunk.tiles[WorldMap::local_index(tile)])
+    }
+
+    pub fn chunk_coords(&self) -> impl Iterator<Item = &IVec2> {
+        self.chunks.keys()
+    }
+
+    /// Queues the chunk to be rendered again, on the terrain and the minimap
+    pub fn mark_dirty(&mut self, chunk: IVec2) {
+        if self.has_chunk(chunk) {
+            self.dirty_chunks.insert(chunk);
+            self.minimap_dirty_chunks.insert(chunk);
+        }
+    }
+
+    /// Terrain transitions cross chunk borders, so a change to a chunk also
+    /// re-renders the chunks around it
+    fn mark_surrounding_dirty(&mut self, chunk: IVec2) {
+        for x in -1..=1 {
+            for y in -1..=1 {
+                self.mark_dirty(chunk + IVec2::new(x, y));
+            }
+        }
+    }
+
+    pub fn take_dirty_chunks(&mut self) -> Vec<IVec2> {
+        self.dirty_chunks.drain().collect()
+    }
+
+    pub fn take_minimap_dirty_chunks(&mut self) -> Vec<IVec2> {
+        self.minimap_dirty_chunks.drain().collect()
+    }
+
+    fn offset(&self) -> Vec2 {
+        if self.mode == WorldMode::Streaming {
+            return Vec2::ZERO;
+        }
+
+        let size = self.block_size;
+
+        Vec2::new(
+            (size * (self.cols as f32 / 2.)) - (size / 2.),
+            (size * (self.rows as f32 / 2.)) - (size / 2.),
+        )
+    }
+
+    /// World position of the centre of `tile`
+    pub fn tile_to_world(&self, tile: IVec2) -> Vec2 {
+        tile.as_vec2() * self.block_size - self.offset()
+    }
+
+    /// The tile coordinate under a world position
+    pub fn world_to_tile(&self, position: Vec2) -> IVec2 {
+        ((position + self.offset()) / self.block_size)
+            .round()
+            .as_ivec2()
+    }
+}
@@ -0,0 +1,202 @@
+use crate::tiles::*;
+use crate::{
+    global_state::{GlobalState, ResourceAbundance, WorldMode},
+    GameState,
+};
+use bevy::prelude::*;
+use noise::{core::perlin::perlin_2d, permutationtable::PermutationTable, utils::*};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+mod chunks;
+mod hydrology;
+mod map;
+mod start_area;
+mod streaming;
+mod transitions;
+
+pub use chunks::ChunkEntities;
+pub use map::{MapTile, WorldMap};
+pub use start_area::{MIN_RESOURCES, RESOURCE_RADIUS};
+
+use chunks::ChunkRenderer;
+
+pub struct WorldGenPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+enum GeneratorSteps {
+    World,
+}
+
+impl Plugin for WorldGenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkEntities>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(WorldGenerator::spawn_world.label(GeneratorSteps::World)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(WorldGenerator::stream_chunks)
+                    .with_system(ChunkRenderer::update_active_chunks),
+            );
+    }
+}
+
+/// Draws the terrain, left out when running without a renderer
+pub struct TerrainRenderPlugin;
+
+impl Plugin for TerrainRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Playing).with_system(ChunkRenderer::build_atlas),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(ChunkRenderer::rebuild_dirty_chunks),
+        );
+    }
+}
+
+/// Generates the whole map for `global_state` without spawning anything
+pub fn generate_map(global_state: &GlobalState) -> WorldMap {
+    match global_state.world_mode {
+        WorldMode::Fixed => WorldGenerator::generate_fixed_map(global_state),
+        WorldMode::Streaming => WorldGenerator::generate_streaming_map(global_state),
+    }
+}
+
+struct WorldGenerator;
+
+/// The map while it is being generated, indexed as `tiles[x][y]`
+struct MapGrid {
+    cols: usize,
+    rows: usize,
+    tiles: Vec<Vec<MapTile>>,
+}
+
+/// Where the player starts, guaranteed to have room for the `TownCentre`
+#[derive(Resource)]
+pub struct StartLocation {
+    pub tile: IVec2,
+    pub position: Vec2,
+}
+
+impl WorldGenerator {
+    fn generate_perlin_atlas(global_state: &GlobalState) -> Vec<Vec<f32>> {
+        let hasher = PermutationTable::new(global_state.seed);
+        let perlin_v2 = PlaneMapBuilder::new_fn(perlin_2d, &hasher)
+            .set_size(global_state.world_cols, global_state.world_rows)
+            .set_x_bounds(0.1, 1.0)
+            .set_y_bounds(0.1, 1.0)
+            .build();
+
+        (0..global_state.world_cols)
+            .map(|x| {
+                (0..global_state.world_rows)
+                    .map(|y| perlin_v2.get_value(x, y) as f32)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    }
+
+    fn generate_tiles(global_state: &GlobalState, rng: &mut impl Rng) -> Vec<Vec<TileType>> {
+        let atlas = WorldGenerator::generate_perlin_atlas(global_state);
+        let elevation = atlas
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|value| global_state.water_level.adjust(*value))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut tiles = elevation
+            .iter()
+            .map(|column| column.iter().map(TileType::from_perlin).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        WorldGenerator::apply_hydrology(&elevation, &mut tiles, rng);
+
+        tiles
+    }
+
+    /// Generates the full map data: terrain, hydrology, resources and a fair
+    /// starting area for the town centre.
+    fn generate_fixed_map(global_state: &GlobalState) -> WorldMap {
+        let mut rng = StdRng::seed_from_u64(global_state.seed as u64);
+        let tiles = WorldGenerator::generate_tiles(global_state, &mut rng);
+
+        let tiles = tiles
+            .into_iter()
+            .map(|column| {
+                column
+                    .into_iter()
+                    .map(|tile_type| {
+                        let mut tile = MapTile::new(tile_type);
+
+                        if tile_type == TileType::Grass {
+                            tile.resource = WorldGenerator::roll_grass_resource(
+                                global_state.resource_abundance,
+                                &mut rng,
+                            );
+                            tile.resource_lvl =
+                                tile.resource.map_or(0, |kind| kind.roll_lvl(&mut rng));
+                        }
+
+                        tile
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut grid = MapGrid {
+            cols: global_state.world_cols,
+            rows: global_state.world_rows,
+            tiles,
+        };
+
+        let (x, y) = WorldGenerator::prepare_start_area(&mut grid, &mut rng);
+
+        WorldMap::from_columns(
+            grid.tiles,
+            global_state.block_size as f32,
+            IVec2::new(x as i32, y as i32),
+        )
+    }
+
+    fn roll_grass_resource(
+        abundance: ResourceAbundance,
+        rng: &mut impl Rng,
+    ) -> Option<ResourceKind> {
+        let roll = rng.gen::<f32>();
+        let percentage = (roll * 100. / abundance.multiplier()) as i32;
+
+        match percentage {
+            0..=15 => Some(ResourceKind::Tree),
+            16..=21 => Some(ResourceKind::Stone),
+            22..=30 => Some(ResourceKind::Berry),
+            _ => None,
+        }
+    }
+
+    fn spawn_world(
+        mut commands: Commands,
+        state: Res<GlobalState>,
+        mut camera_query: Query<&mut Transform, With<Camera>>,
+    ) {
+        let map = generate_map(&state);
+
+        let start = StartLocation {
+            tile: map.start,
+            position: map.tile_to_world(map.start),
+        };
+
+        for mut transform in camera_query.iter_mut() {
+            transform.translation.x = start.position.x;
+            transform.translation.y = start.position.y;
+        }
+
+        commands.insert_resource(start);
+        commands.insert_resource(map);
+    }
+}
@@ -0,0 +1,126 @@
+use rand::{seq::SliceRandom, Rng};
+
+use super::{MapGrid, WorldGenerator};
+use crate::tiles::{ResourceKind, TileType};
+
+/// Tiles around the start location that are cleared for the `TownCentre`
+const CLEAR_RADIUS: i32 = 1;
+/// Radius around the start location that the minimum resources are placed within
+pub const RESOURCE_RADIUS: i32 = 6;
+/// Resources there are at least this many of within `RESOURCE_RADIUS`
+pub const MIN_RESOURCES: [(ResourceKind, usize); 3] = [
+    (ResourceKind::Tree, 6),
+    (ResourceKind::Stone, 3),
+    (ResourceKind::Berry, 4),
+];
+
+impl WorldGenerator {
+    /// Picks the start location, clears room for the `TownCentre` and tops up
+    /// the nearby resources. Returns the start tile.
+    pub(super) fn prepare_start_area(map: &mut MapGrid, rng: &mut impl Rng) -> (usize, usize) {
+        let start = WorldGenerator::find_start(map);
+
+        WorldGenerator::clear_start(map, start);
+        WorldGenerator::ensure_start_resources(map, start, rng);
+
+        start
+    }
+
+    /// The closest tile to the middle of the map (where the camera starts) that
+    /// has no water around it. Falls back to the middle of the map.
+    fn find_start(map: &MapGrid) -> (usize, usize) {
+        let centre = ((map.cols / 2) as i32, (map.rows / 2) as i32);
+        let mut best: Option<((i32, i32), i32)> = None;
+
+        for x in CLEAR_RADIUS..map.cols as i32 - CLEAR_RADIUS {
+            for y in CLEAR_RADIUS..map.rows as i32 - CLEAR_RADIUS {
+                let is_dry = WorldGenerator::tiles_within(map, (x, y), CLEAR_RADIUS)
+                    .iter()
+                    .all(|&(tx, ty)| map.tiles[tx][ty].tile_type != TileType::Water);
+
+                if !is_dry {
+                    continue;
+                }
+
+                let distance = (x - centre.0).pow(2) + (y - centre.1).pow(2);
+                if best.is_none_or(|(_, closest)| distance < closest) {
+                    best = Some(((x, y), distance));
+                }
+            }
+        }
+
+        let ((x, y), _) = best.unwrap_or((centre, 0));
+        (x as usize, y as usize)
+    }
+
+    /// Turns the tiles around the start into empty buildable land
+    fn clear_start(map: &mut MapGrid, start: (usize, usize)) {
+        let start = (start.0 as i32, start.1 as i32);
+
+        for (x, y) in WorldGenerator::tiles_within(map, start, CLEAR_RADIUS) {
+            let tile = &mut map.tiles[x][y];
+
+            if !matches!(tile.tile_type, TileType::Grass | TileType::Dirt) {
+                tile.tile_type = TileType::Grass;
+            }
+            tile.resource = None;
+        }
+    }
+
+    fn ensure_start_resources(map: &mut MapGrid, start: (usize, usize), rng: &mut impl Rng) {
+        let start = (start.0 as i32, start.1 as i32);
+        let nearby = WorldGenerator::tiles_within(map, start, RESOURCE_RADIUS)
+            .into_iter()
+            .filter(|&(x, y)| {
+                let (dx, dy) = (x as i32 - start.0, y as i32 - start.1);
+                dx * dx + dy * dy <= RESOURCE_RADIUS * RESOURCE_RADIUS
+            })
+            .collect::<Vec<_>>();
+
+        for (kind, minimum) in MIN_RESOURCES {
+            let count = nearby
+                .iter()
+                .filter(|&&(x, y)| map.tiles[x][y].resource == Some(kind))
+                .count();
+
+            if count >= minimum {
+                continue;
+            }
+
+            let mut candidates = nearby
+                .iter()
+                .copied()
+                .filter(|&(x, y)| {
+                    let in_clearing = (x as i32 - start.0).abs() <= CLEAR_RADIUS
+                        && (y as i32 - start.1).abs() <= CLEAR_RADIUS;
+                    let tile = &map.tiles[x][y];
+
+                    !in_clearing && tile.resource.is_none() && tile.tile_type != TileType::Water
+                })
+                .collect::<Vec<_>>();
+            candidates.shuffle(rng);
+
+            for (x, y) in candidates.into_iter().take(minimum - count) {
+                let tile = &mut map.tiles[x][y];
+                tile.tile_type = TileType::Grass;
+                tile.resource = Some(kind);
+                tile.resource_lvl = kind.roll_lvl(rng);
+            }
+        }
+    }
+
+    /// All tiles in the square of `radius` around `centre` that are on the map
+    fn tiles_within(map: &MapGrid, centre: (i32, i32), radius: i32) -> Vec<(usize, usize)> {
+        let mut tiles = vec![];
+
+        for x in centre.0 - radius..=centre.0 + radius {
+            for y in centre.1 - radius..=centre.1 + radius {
+                if x >= 0 && y >= 0 && x < map.cols as i32 && y < map.rows as i32 {
+                    tiles.push((x as usize, y as usize));
+                }
+            }
+        }
+
+        tiles
+    }
+}
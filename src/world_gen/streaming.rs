@@ -0,0 +1,199 @@
+//! Endless worlds, generated one chunk at a time as the camera explores.
+//!
+//! Every chunk is a pure function of the seed and its chunk coordinate, so the
+//! same seed always produces the same world no matter the order it is explored
+//! in. Lakes and rivers need the whole map to trace, so an endless world only
+//! gets the noise terrain with sand banks along the water.
+
+use bevy::prelude::*;
+use noise::{core::perlin::perlin_2d, permutationtable::PermutationTable};
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::{
+    chunks::ChunkRenderer,
+    map::{MapTile, WorldMap, CHUNK_SIZE},
+    MapGrid, WorldGenerator,
+};
+use crate::{
+    global_state::{GlobalState, WorldMode},
+    tiles::*,
+};
+
+/// Distance in noise space between neighbouring tiles, matches a medium fixed map
+const NOISE_SCALE: f64 = 0.9 / 64.;
+/// Keeps tile `(0, 0)` off the noise lattice, where perlin noise is always zero
+const NOISE_OFFSET: f64 = 0.1;
+/// Chunks this far outside of the camera view are generated ahead of time
+const GENERATE_MARGIN: i32 = 2;
+/// Caps the work done in a single frame while panning quickly
+const MAX_CHUNKS_PER_FRAME: usize = 4;
+/// Chunks around the origin generated up front to find the start area in
+const START_RADIUS: i32 = 1;
+
+const SURROUNDING: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl WorldGenerator {
+    /// Generates the chunks around the origin and prepares the start area in them
+    pub(super) fn generate_streaming_map(global_state: &GlobalState) -> WorldMap {
+        let hasher = PermutationTable::new(global_state.seed);
+        let mut rng = StdRng::seed_from_u64(global_state.seed as u64);
+        let mut map = WorldMap::streaming(global_state.block_size as f32);
+
+        for x in -START_RADIUS..=START_RADIUS {
+            for y in -START_RADIUS..=START_RADIUS {
+                let chunk = IVec2::new(x, y);
+                let tiles = WorldGenerator::generate_chunk(global_state, &hasher, chunk);
+                map.insert_chunk(chunk, tiles);
+            }
+        }
+
+        let size = ((START_RADIUS * 2 + 1) * CHUNK_SIZE) as usize;
+        let origin = IVec2::splat(-START_RADIUS * CHUNK_SIZE);
+        let tiles = (0..size)
+            .map(|x| {
+                (0..size)
+                    .map(|y| {
+                        let tile = origin + IVec2::new(x as i32, y as i32);
+                        map.get(tile)
+                            .copied()
+                            .unwrap_or(MapTile::new(TileType::Water))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut grid = MapGrid {
+            cols: size,
+            rows: size,
+            tiles,
+        };
+
+        let (x, y) = WorldGenerator::prepare_start_area(&mut grid, &mut rng);
+
+        map.insert_columns(origin, grid.tiles);
+        map.start = origin + IVec2::new(x as i32, y as i32);
+        map
+    }
+
+    /// Terrain and resources of a single chunk, indexed as `y * CHUNK_SIZE + x`
+    fn generate_chunk(
+        global_state: &GlobalState,
+        hasher: &PermutationTable,
+        chunk: IVec2,
+    ) -> Vec<MapTile> {
+        let mut rng = StdRng::seed_from_u64(WorldGenerator::chunk_seed(global_state.seed, chunk));
+
+        WorldMap::chunk_tiles(chunk)
+            .map(|tile| {
+                let tile_type = WorldGenerator::streaming_tile_type(global_state, hasher, tile);
+                let mut map_tile = MapTile::new(tile_type);
+
+                if tile_type == TileType::Grass {
+                    map_tile.resource = WorldGenerator::roll_grass_resource(
+                        global_state.resource_abundance,
+                        &mut rng,
+                    );
+                    map_tile.resource_lvl =
+                        map_tile.resource.map_or(0, |kind| kind.roll_lvl(&mut rng));
+                }
+
+                map_tile
+            })
+            .collect()
+    }
+
+    fn chunk_seed(seed: u32, chunk: IVec2) -> u64 {
+        let x = chunk.x as u32 as u64;
+        let y = chunk.y as u32 as u64;
+
+        ((seed as u64) << 32)
+            ^ x.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ y.wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+    }
+
+    fn streaming_elevation(
+        global_state: &GlobalState,
+        hasher: &PermutationTable,
+        tile: IVec2,
+    ) -> f32 {
+        let point = [
+            tile.x as f64 * NOISE_SCALE + NOISE_OFFSET,
+            tile.y as f64 * NOISE_SCALE + NOISE_OFFSET,
+        ];
+
+        global_state
+            .water_level
+            .adjust(perlin_2d(point, hasher) as f32)
+    }
+
+    /// Same bands as a fixed map, with grass and dirt next to water turned to sand
+    fn streaming_tile_type(
+        global_state: &GlobalState,
+        hasher: &PermutationTable,
+        tile: IVec2,
+    ) -> TileType {
+        let elevation = WorldGenerator::streaming_elevation(global_state, hasher, tile);
+        let tile_type = TileType::from_perlin(&elevation);
+
+        if tile_type != TileType::Grass && tile_type != TileType::Dirt {
+            return tile_type;
+        }
+
+        let near_water = SURROUNDING.iter().any(|(dx, dy)| {
+            let neighbour = tile + IVec2::new(*dx, *dy);
+            let elevation = WorldGenerator::streaming_elevation(global_state, hasher, neighbour);
+
+            TileType::from_perlin(&elevation) == TileType::Water
+        });
+
+        match near_water {
+            true => TileType::Sand,
+            false => tile_type,
+        }
+    }
+
+    /// Generates the chunks nearing the camera that haven't been explored yet
+    pub(super) fn stream_chunks(
+        global_state: Res<GlobalState>,
+        map: Option<ResMut<WorldMap>>,
+        camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    ) {
+        let (Some(mut map), Ok((camera, projection))) = (map, camera_query.get_single()) else {
+            return;
+        };
+
+        if map.mode != WorldMode::Streaming {
+            return;
+        }
+
+        let (min, max) = ChunkRenderer::chunks_in_view(&map, camera, projection, GENERATE_MARGIN);
+        let centre = WorldMap::chunk_of(map.world_to_tile(camera.translation.truncate()));
+
+        let mut missing = (min.x..=max.x)
+            .flat_map(|x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter(|chunk| !map.has_chunk(*chunk))
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return;
+        }
+
+        missing.sort_by_key(|chunk| (*chunk - centre).abs().max_element());
+
+        let hasher = PermutationTable::new(global_state.seed);
+
+        for chunk in missing.into_iter().take(MAX_CHUNKS_PER_FRAME) {
+            let tiles = WorldGenerator::generate_chunk(&global_state, &hasher, chunk);
+            map.insert_chunk(chunk, tiles);
+        }
+    }
+}
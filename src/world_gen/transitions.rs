@@ -0,0 +1,139 @@
+//! Autotiling: soft edges where two kinds of terrain meet.
+//!
+//! Every terrain type has a blend priority. A tile gets an overlay for each
+//! neighbour with a higher priority, made from that neighbour's texture faded
+//! out towards the middle of the tile. Edges cover the sides they touch,
+//! corners only fill in the diagonals not already covered by an edge.
+
+use bevy::{prelude::*, render::render_resource::TextureDimension};
+
+use super::map::WorldMap;
+use crate::tiles::TileType;
+
+/// How far into a tile a neighbour fades, as a fraction of the tile size
+const FADE_DEPTH: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Transition {
+    North,
+    East,
+    South,
+    West,
+    NorthEast,
+    SouthEast,
+    SouthWest,
+    NorthWest,
+}
+
+impl Transition {
+    pub const ALL: [Transition; 8] = [
+        Transition::North,
+        Transition::East,
+        Transition::South,
+        Transition::West,
+        Transition::NorthEast,
+        Transition::SouthEast,
+        Transition::SouthWest,
+        Transition::NorthWest,
+    ];
+
+    /// Offset to the neighbouring tile this transition blends in from
+    fn offset(&self) -> IVec2 {
+        match self {
+            Transition::North => IVec2::new(0, 1),
+            Transition::East => IVec2::new(1, 0),
+            Transition::South => IVec2::new(0, -1),
+            Transition::West => IVec2::new(-1, 0),
+            Transition::NorthEast => IVec2::new(1, 1),
+            Transition::SouthEast => IVec2::new(1, -1),
+            Transition::SouthWest => IVec2::new(-1, -1),
+            Transition::NorthWest => IVec2::new(-1, 1),
+        }
+    }
+
+    /// The two edges either side of a corner
+    fn corner_sides(&self) -> Option<(Transition, Transition)> {
+        match self {
+            Transition::NorthEast => Some((Transition::North, Transition::East)),
+            Transition::SouthEast => Some((Transition::South, Transition::East)),
+            Transition::SouthWest => Some((Transition::South, Transition::West)),
+            Transition::NorthWest => Some((Transition::North, Transition::West)),
+            _ => None,
+        }
+    }
+
+    /// Opacity of the overlay at `uv`, measured from the top left of the tile
+    fn alpha(&self, uv: Vec2) -> f32 {
+        let distance = match self {
+            Transition::North => uv.y,
+            Transition::East => 1. - uv.x,
+            Transition::South => 1. - uv.y,
+            Transition::West => uv.x,
+            Transition::NorthEast => Vec2::new(1. - uv.x, uv.y).length(),
+            Transition::SouthEast => Vec2::new(1. - uv.x, 1. - uv.y).length(),
+            Transition::SouthWest => Vec2::new(uv.x, 1. - uv.y).length(),
+            Transition::NorthWest => uv.length(),
+        };
+
+        (1. - distance / FADE_DEPTH).clamp(0., 1.)
+    }
+
+    /// A copy of `texture` faded out away from this edge or corner. Only
+    /// textures with four bytes per pixel can be faded.
+    pub fn overlay(&self, texture: &Image) -> Option<Image> {
+        let size = texture.texture_descriptor.size;
+        let (width, height) = (size.width as usize, size.height as usize);
+
+        if texture.data.len() != width * height * 4 {
+            return None;
+        }
+
+        let mut data = texture.data.clone();
+
+        for (index, pixel) in data.chunks_exact_mut(4).enumerate() {
+            let uv = Vec2::new((index % width) as f32 + 0.5, (index / width) as f32 + 0.5)
+                / Vec2::new(width as f32, height as f32);
+
+            pixel[3] = (pixel[3] as f32 * self.alpha(uv)) as u8;
+        }
+
+        Some(Image::new(
+            size,
+            TextureDimension::D2,
+            data,
+            texture.texture_descriptor.format,
+        ))
+    }
+
+    /// The overlays to draw on top of `tile`, lowest priority first
+    pub fn for_tile(map: &WorldMap, tile: IVec2) -> Vec<(TileType, Transition)> {
+        let Some(centre) = map.get(tile) else {
+            return vec![];
+        };
+
+        let higher_neighbour = |transition: Transition| {
+            map.get(tile + transition.offset())
+                .map(|neighbour| neighbour.tile_type)
+                .filter(|tile_type| tile_type.blend_priority() > centre.tile_type.blend_priority())
+        };
+
+        let mut overlays = vec![];
+
+        for transition in Transition::ALL {
+            let Some(tile_type) = higher_neighbour(transition) else {
+                continue;
+            };
+
+            let covered = transition.corner_sides().is_some_and(|(a, b)| {
+                higher_neighbour(a) == Some(tile_type) || higher_neighbour(b) == Some(tile_type)
+            });
+
+            if !covered {
+                overlays.push((tile_type, transition));
+            }
+        }
+
+        overlays.sort_by_key(|(tile_type, _)| tile_type.blend_priority());
+        overlays
+    }
+}
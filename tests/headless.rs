@@ -0,0 +1,1025 @@
+use bevy::prelude::*;
+use game_growth::{
+    building::{
+        Blueprint, Building, BuildingType, Facing, HealthBar, HistoryStep, PlacementError,
+        RepairQueue, RoadAccess, RoadNetwork, Territory, TownCentre, UNDO_WINDOW,
+    },
+    cli::LaunchOptions,
+    director::{EventDirector, RandomEvent, Wildfire, DAY_SECONDS},
+    economy::{BuyGoods, Good, Market, Production, Stockpile},
+    global_state::GlobalState,
+    headless::HeadlessRunner,
+    map_export,
+    minimap::MinimapView,
+    notifications::{NotificationLog, Severity},
+    objectives::{Objective, Objectives, Outcome},
+    research::{Research, Tech},
+    save::SaveGame,
+    tiles::{TilePosition, TileType},
+    units::{
+        Attacker, CommandUnits, Health, Raider, Raids, Soldier, TrainSoldier, UnitOrder, Walker,
+        Worker, SOLDIER_COST,
+    },
+    world_gen::{StartLocation, WorldMap, MIN_RESOURCES, RESOURCE_RADIUS},
+};
+
+fn runner(seed: u32) -> HeadlessRunner {
+    let mut runner = HeadlessRunner::new(GlobalState {
+        seed,
+        world_cols: 48,
+        world_rows: 32,
+        ..GlobalState::default()
+    });
+
+    runner.run_frames(1);
+    runner
+}
+
+fn buildings(runner: &mut HeadlessRunner) -> Vec<(BuildingType, IVec2)> {
+    let world = runner.world();
+    let mut query = world.query_filtered::<(&BuildingType, &TilePosition), With<Building>>();
+
+    query
+        .iter(world)
+        .map(|(building, tile)| (*building, tile.0))
+        .collect()
+}
+
+#[test]
+fn generates_the_world_on_the_first_frame() {
+    let mut runner = runner(7);
+    let map = runner.world().resource::<WorldMap>();
+
+    assert_eq!((map.cols, map.rows), (48, 32));
+    assert!(BuildingType::TownCentre
+        .check_placement(map, &Territory::default(), map.start, Facing::North)
+        .is_ok());
+}
+
+#[test]
+fn the_start_has_resources_nearby() {
+    for seed in [1, 7, 42, 1234] {
+        let mut runner = runner(seed);
+        let start = runner.world().resource::<StartLocation>().tile;
+        let map = runner.world().resource::<WorldMap>();
+        let nearby = (-RESOURCE_RADIUS..=RESOURCE_RADIUS)
+            .flat_map(|x| (-RESOURCE_RADIUS..=RESOURCE_RADIUS).map(move |y| IVec2::new(x, y)))
+            .filter(|offset| offset.dot(*offset) <= RESOURCE_RADIUS * RESOURCE_RADIUS)
+            .filter_map(|offset| map.get(start + offset))
+            .collect::<Vec<_>>();
+
+        for (kind, minimum) in MIN_RESOURCES {
+            let count = nearby
+                .iter()
+                .filter(|tile| tile.resource == Some(kind))
+                .count();
+            assert!(count >= minimum, "seed {} has {} {:?}", seed, count, kind);
+        }
+    }
+}
+
+#[test]
+fn same_seed_generates_the_same_world() {
+    let mut first = runner(42);
+    let mut second = runner(42);
+
+    assert_eq!(
+        map_export::render_ascii(first.world().resource::<WorldMap>()),
+        map_export::render_ascii(second.world().resource::<WorldMap>()),
+    );
+}
+
+#[test]
+fn places_the_town_centre_on_the_start_tile() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+
+    assert_eq!(
+        buildings(&mut runner),
+        vec![(BuildingType::TownCentre, start)]
+    );
+    assert!(
+        runner
+            .world()
+            .resource::<WorldMap>()
+            .get(start)
+            .unwrap()
+            .occupied
+    );
+}
+
+#[test]
+fn ignores_buildings_on_unbuildable_tiles() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let sand = start + IVec2::new(2, 0);
+    let stone = start + IVec2::new(2, 2);
+    let water = start + IVec2::new(2, -2);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+
+    // Inside the territory, so only the terrain is in the way
+    let world = runner.world();
+    let mut map = world.resource_mut::<WorldMap>();
+    for (anchor, tile_type) in [
+        (sand, TileType::Sand),
+        (stone, TileType::Stone),
+        (water, TileType::Water),
+    ] {
+        for tile in [anchor, anchor + IVec2::X] {
+            let map_tile = map.get_mut(tile).unwrap();
+            map_tile.tile_type = tile_type;
+            map_tile.resource = None;
+            map_tile.occupied = false;
+        }
+    }
+
+    let (map, territory) = (world.resource::<WorldMap>(), world.resource::<Territory>());
+    for anchor in [sand, stone, water] {
+        assert!(territory.contains(anchor) && territory.contains(anchor + IVec2::X));
+        assert_eq!(
+            BuildingType::Farm.check_placement(map, territory, anchor, Facing::North),
+            Err(PlacementError::WrongTerrain(
+                BuildingType::Farm,
+                &[TileType::Grass, TileType::Dirt]
+            ))
+        );
+    }
+    assert!(BuildingType::House
+        .check_placement(map, territory, stone, Facing::North)
+        .is_ok());
+    assert!(BuildingType::Road
+        .check_placement(map, territory, sand, Facing::North)
+        .is_ok());
+    assert_eq!(
+        BuildingType::House.check_placement(map, territory, sand, Facing::North),
+        Err(PlacementError::WrongTerrain(
+            BuildingType::House,
+            &[TileType::Grass, TileType::Dirt, TileType::Stone]
+        ))
+    );
+
+    runner.place_building(BuildingType::Farm, start);
+    for anchor in [sand, stone, water] {
+        runner.place_building(BuildingType::Farm, anchor);
+    }
+    runner.run_frames(2);
+
+    assert_eq!(
+        buildings(&mut runner),
+        vec![(BuildingType::TownCentre, start)]
+    );
+}
+
+#[test]
+fn only_builds_inside_the_territory() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let far_away = start + IVec2::new(20, 0);
+
+    runner.place_building(BuildingType::House, start);
+    runner.run_frames(2);
+    assert!(buildings(&mut runner).is_empty());
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.place_building(BuildingType::House, start + IVec2::new(2, 0));
+    runner.place_building(BuildingType::House, far_away);
+    runner.run_frames(2);
+
+    let territory = runner.world().resource::<Territory>();
+    assert!(territory.contains(start + IVec2::new(2, 0)));
+    assert!(!territory.contains(far_away));
+    assert_eq!(buildings(&mut runner).len(), 2);
+}
+
+#[test]
+fn buildings_need_a_road_to_the_town_centre() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.place_building(BuildingType::House, start + IVec2::new(2, 0));
+    runner.run_frames(2);
+
+    let has_access = |runner: &mut HeadlessRunner| {
+        let world = runner.world();
+        let mut query = world.query_filtered::<&BuildingType, With<RoadAccess>>();
+        query
+            .iter(world)
+            .any(|building| *building == BuildingType::House)
+    };
+    assert!(!has_access(&mut runner));
+
+    runner.place_building(BuildingType::Road, start + IVec2::new(1, 0));
+    runner.run_frames(2);
+
+    let network = runner.world().resource::<RoadNetwork>();
+    assert!(network.is_connected(start + IVec2::new(1, 0)));
+    assert!(has_access(&mut runner));
+}
+
+#[test]
+fn buildings_are_paid_for_from_the_stockpile() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner
+        .world()
+        .resource_mut::<Stockpile>()
+        .set(Good::Wood, 30);
+    runner.place_building(BuildingType::House, start + IVec2::new(2, 0));
+    runner.place_building(BuildingType::House, start + IVec2::new(-2, 0));
+    runner.run_frames(2);
+
+    assert_eq!(
+        runner.world().resource::<Stockpile>().amount(Good::Wood),
+        10
+    );
+    assert_eq!(buildings(&mut runner).len(), 2);
+}
+
+#[test]
+fn undoes_and_redoes_placing_and_demolishing() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let house = start + IVec2::new(2, 0);
+    let wood =
+        |runner: &mut HeadlessRunner| runner.world().resource::<Stockpile>().amount(Good::Wood);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    let starting_wood = wood(&mut runner);
+
+    runner.place_building(BuildingType::House, house);
+    runner.run_frames(2);
+    assert_eq!(buildings(&mut runner).len(), 2);
+
+    runner.step_history(HistoryStep::Undo);
+    runner.run_frames(2);
+    assert_eq!(buildings(&mut runner).len(), 1);
+    assert_eq!(wood(&mut runner), starting_wood);
+
+    runner.step_history(HistoryStep::Redo);
+    runner.run_frames(2);
+    assert_eq!(buildings(&mut runner).len(), 2);
+
+    runner.demolish_building(house);
+    runner.run_frames(2);
+    assert_eq!(buildings(&mut runner).len(), 1);
+    assert!(
+        !runner
+            .world()
+            .resource::<WorldMap>()
+            .get(house)
+            .unwrap()
+            .occupied
+    );
+
+    runner.step_history(HistoryStep::Undo);
+    runner.run_frames(2);
+    assert!(buildings(&mut runner).contains(&(BuildingType::House, house)));
+
+    // Undoing an upgrade of a finished house leaves it finished
+    runner.run_seconds(UNDO_WINDOW + 1.0);
+    runner
+        .world()
+        .resource_mut::<Research>()
+        .complete(Tech::Carpentry);
+    let mut stockpile = runner.world().resource_mut::<Stockpile>();
+    stockpile.set(Good::Wood, 100);
+    stockpile.set(Good::Planks, 100);
+    let is_blueprint = |runner: &mut HeadlessRunner| {
+        let world = runner.world();
+        let mut query = world.query::<(&TilePosition, Option<&Blueprint>)>();
+        query
+            .iter(world)
+            .any(|(tile, blueprint)| tile.0 == house && blueprint.is_some())
+    };
+    assert!(!is_blueprint(&mut runner));
+
+    runner.upgrade_building(house);
+    runner.run_frames(2);
+    assert!(buildings(&mut runner).contains(&(BuildingType::HouseOne, house)));
+    assert!(is_blueprint(&mut runner));
+
+    runner.step_history(HistoryStep::Undo);
+    runner.run_frames(2);
+    assert!(buildings(&mut runner).contains(&(BuildingType::House, house)));
+    assert!(!is_blueprint(&mut runner));
+}
+
+#[test]
+fn turned_farms_cover_different_tiles() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+
+    // Anywhere near the town centre the farm fits on its side
+    let world = runner.world();
+    let (map, territory) = (world.resource::<WorldMap>(), world.resource::<Territory>());
+    let farm = (-3..=3)
+        .flat_map(|x| (-3..=3).map(move |y| start + IVec2::new(x, y)))
+        .find(|tile| {
+            BuildingType::Farm
+                .check_placement(map, territory, *tile, Facing::East)
+                .is_ok()
+                && map.get(*tile + IVec2::X).is_some_and(|tile| !tile.occupied)
+        })
+        .expect("room for a farm");
+    runner.place_building_facing(BuildingType::Farm, farm, Facing::East);
+    runner.run_frames(2);
+
+    let map = runner.world().resource::<WorldMap>();
+    assert!(map.get(farm + IVec2::new(0, 1)).unwrap().occupied);
+    assert!(!map.get(farm + IVec2::new(1, 0)).unwrap().occupied);
+}
+
+#[test]
+fn lumber_mills_turn_wood_into_planks() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let mill = start + IVec2::new(2, 0);
+    let planks =
+        |runner: &mut HeadlessRunner| runner.world().resource::<Stockpile>().amount(Good::Planks);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner
+        .world()
+        .resource_mut::<Research>()
+        .complete(Tech::Forestry);
+    runner.place_building(BuildingType::Road, start + IVec2::new(1, 0));
+    runner.place_building(BuildingType::LumberMill, mill);
+    runner.run_frames(2);
+    assert_eq!(planks(&mut runner), 0);
+
+    // Construction, the workers walking over, a batch and a cart
+    runner.run_seconds(UNDO_WINDOW + 15.0);
+    assert!(planks(&mut runner) > 0);
+
+    // Tearing it down returns whatever is waiting in its buffers, without
+    // workers no batch changes them in the meantime
+    let world = runner.world();
+    let workers = world
+        .query_filtered::<Entity, With<Worker>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for worker in workers {
+        world.despawn(worker);
+    }
+    runner.run_frames(1);
+
+    let world = runner.world();
+    let buffered = world.query::<&Production>().single(world).buffered_goods();
+    let stockpile = world.resource::<Stockpile>().clone();
+    assert!(!buffered.is_empty());
+    runner.demolish_building(mill);
+    runner.run_frames(1);
+
+    let world = runner.world();
+    assert!(world.query::<&Production>().iter(world).next().is_none());
+    for (good, amount) in buffered {
+        assert_eq!(
+            world.resource::<Stockpile>().amount(good),
+            stockpile.amount(good) + amount
+        );
+    }
+}
+
+#[test]
+fn a_good_harvest_needs_room_for_twice_the_food() {
+    let mut farm = Production::new(BuildingType::Farm.recipe().unwrap());
+    let (good, amount) = farm.recipe.outputs[0];
+
+    // Room for one more batch, but not for a doubled one
+    farm.output.insert(good, amount * 2);
+    assert!(farm.can_run(1));
+    assert!(!farm.can_run(2));
+
+    farm.output.insert(good, amount);
+    assert!(farm.can_run(2));
+}
+
+#[test]
+fn staffed_stalls_sell_surplus_and_buy_scarce_goods() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let amount =
+        |runner: &mut HeadlessRunner, good| runner.world().resource::<Stockpile>().amount(good);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner
+        .world()
+        .resource_mut::<Research>()
+        .complete(Tech::Trade);
+    runner.place_building(BuildingType::Road, start + IVec2::new(1, 0));
+    runner.place_building(BuildingType::Stall, start + IVec2::new(2, 0));
+    runner.run_frames(2);
+    runner
+        .world()
+        .resource_mut::<Stockpile>()
+        .set(Good::Wood, 400);
+
+    // Construction, the worker walking over and a batch, however long the
+    // walk turns out to be
+    for _ in 0..(UNDO_WINDOW + 60.0) as usize {
+        if amount(&mut runner, Good::Coin) > 0 {
+            break;
+        }
+        runner.run_seconds(1.0);
+    }
+    assert!(runner.world().resource::<Market>().is_open());
+    assert!(amount(&mut runner, Good::Wood) < 400);
+    assert!(amount(&mut runner, Good::Coin) > 0);
+
+    // Planks are scarce, so they cost more than their base price
+    runner
+        .world()
+        .resource_mut::<Stockpile>()
+        .set(Good::Coin, 1000);
+    runner.run_frames(1);
+    let cost = runner
+        .world()
+        .resource::<Market>()
+        .buy_cost(Good::Planks, 10);
+    assert!(cost > 40);
+
+    runner.world().send_event(BuyGoods {
+        good: Good::Planks,
+        amount: 10,
+    });
+    runner.run_frames(1);
+    assert_eq!(amount(&mut runner, Good::Planks), 10);
+    assert_eq!(amount(&mut runner, Good::Coin), 1000 - cost);
+}
+
+#[test]
+fn barracks_train_soldiers_that_follow_orders() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let barracks = start + IVec2::new(2, 0);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner
+        .world()
+        .resource_mut::<Research>()
+        .complete(Tech::Militia);
+    runner.place_building(BuildingType::Barracks, barracks);
+    runner.run_seconds(UNDO_WINDOW + 1.0);
+
+    let mut stockpile = runner.world().resource_mut::<Stockpile>();
+    stockpile.set(Good::Food, 100);
+    stockpile.set(Good::Coin, 100);
+    runner.world().send_event(TrainSoldier { tile: barracks });
+    runner.run_frames(2);
+
+    let world = runner.world();
+    let soldier = world
+        .query_filtered::<Entity, With<Soldier>>()
+        .single(world);
+    assert_eq!(world.query::<&Worker>().iter(world).count(), 2);
+    assert_eq!(
+        world.resource::<Stockpile>().amount(Good::Coin),
+        100 - SOLDIER_COST[1].1
+    );
+
+    // Somewhere walkable the soldier can be sent to
+    let (map, network) = (
+        world.resource::<WorldMap>(),
+        world.resource::<RoadNetwork>(),
+    );
+    let target = (-4..=4)
+        .flat_map(|x| (-4..=4).map(move |y| start + IVec2::new(x, y)))
+        .filter(|tile| (*tile - start).abs().max_element() > 2)
+        .find(|tile| Walker::can_walk(map, network, *tile))
+        .expect("somewhere to walk to");
+    runner.world().send_event(CommandUnits {
+        units: vec![soldier],
+        order: UnitOrder::MoveTo(target),
+    });
+    runner.run_seconds(15.0);
+
+    let tile = runner.world().get::<TilePosition>(soldier).unwrap().0;
+    let offset = (tile - target).abs();
+    assert!(offset.max_element() <= 3);
+}
+
+#[test]
+fn nobody_is_sent_to_an_entrance_they_cannot_reach() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let site = start + IVec2::new(2, 0);
+    let island = start + IVec2::new(0, -4);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    let mut research = runner.world().resource_mut::<Research>();
+    research.complete(Tech::Forestry);
+    research.complete(Tech::Militia);
+
+    // Every worker on a tile with water all around it
+    let world = runner.world();
+    let mut map = world.resource_mut::<WorldMap>();
+    for side in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+        map.get_mut(island + side).unwrap().tile_type = TileType::Water;
+    }
+    for mut tile in world
+        .query_filtered::<&mut TilePosition, With<Worker>>()
+        .iter_mut(world)
+    {
+        tile.0 = island;
+    }
+
+    runner.place_building(BuildingType::Road, start + IVec2::new(1, 0));
+    runner.place_building(BuildingType::LumberMill, site);
+    runner.run_seconds(UNDO_WINDOW + 15.0);
+
+    let world = runner.world();
+    assert!(world
+        .query::<&Worker>()
+        .iter(world)
+        .all(|worker| worker.workplace.is_none()));
+    assert_eq!(world.resource::<Stockpile>().amount(Good::Planks), 0);
+
+    runner.demolish_building(site);
+    runner.run_frames(1);
+    runner.place_building(BuildingType::Barracks, site);
+    runner.run_seconds(UNDO_WINDOW + 1.0);
+
+    let mut stockpile = runner.world().resource_mut::<Stockpile>();
+    stockpile.set(Good::Food, 100);
+    stockpile.set(Good::Coin, 100);
+    runner.world().send_event(TrainSoldier { tile: site });
+    runner.run_frames(2);
+
+    let world = runner.world();
+    assert_eq!(world.query::<&Soldier>().iter(world).count(), 0);
+    assert_eq!(world.resource::<Stockpile>().amount(Good::Coin), 100);
+}
+
+#[test]
+fn raiders_come_from_the_map_edge_for_the_town_centre() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.world().resource_mut::<Raids>().call_next_wave();
+    runner.run_frames(2);
+
+    let world = runner.world();
+    let town_centre = world
+        .query_filtered::<Entity, With<TownCentre>>()
+        .single(world);
+    let raiders = world
+        .query_filtered::<(&TilePosition, &Attacker), With<Raider>>()
+        .iter(world)
+        .map(|(tile, attacker)| (tile.0, attacker.target))
+        .collect::<Vec<_>>();
+
+    assert!(!raiders.is_empty());
+    assert_eq!(world.resource::<Raids>().waves, 1);
+    for (tile, target) in raiders {
+        assert!(tile.x == 0 || tile.y == 0 || tile.x == 47 || tile.y == 31);
+        assert_eq!(target, Some(town_centre));
+    }
+}
+
+#[test]
+fn raiders_hold_their_ground_when_they_cannot_reach_the_town_centre() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let island = start + IVec2::new(0, -6);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.world().resource_mut::<Raids>().call_next_wave();
+    runner.run_frames(2);
+
+    // Every raider on a tile with water all around it
+    let world = runner.world();
+    let mut map = world.resource_mut::<WorldMap>();
+    for side in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+        map.get_mut(island + side).unwrap().tile_type = TileType::Water;
+    }
+    for (mut tile, mut walker) in world
+        .query_filtered::<(&mut TilePosition, &mut Walker), With<Raider>>()
+        .iter_mut(world)
+    {
+        tile.0 = island;
+        walker.path.clear();
+    }
+    runner.run_seconds(5.0);
+
+    let world = runner.world();
+    assert!(world
+        .query_filtered::<(&TilePosition, &Walker), With<Raider>>()
+        .iter(world)
+        .all(|(tile, walker)| tile.0 == island && walker.is_idle()));
+}
+
+#[test]
+fn damaged_buildings_are_repaired_or_destroyed() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let house_tile = start + IVec2::new(4, 0);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.place_building(BuildingType::House, house_tile);
+    runner.run_seconds(UNDO_WINDOW + 1.0);
+
+    let world = runner.world();
+    let house = world
+        .query_filtered::<(Entity, &TilePosition), With<Building>>()
+        .iter(world)
+        .find(|(_, tile)| tile.0 == house_tile)
+        .map(|(entity, _)| entity)
+        .expect("house placed");
+    let wood = world.resource::<Stockpile>().amount(Good::Wood);
+
+    world.get_mut::<Health>(house).unwrap().damage(75);
+    runner.run_frames(2);
+    let world = runner.world();
+    assert_eq!(world.query::<&HealthBar>().iter(world).count(), 1);
+
+    runner.repair_building(house_tile);
+    runner.run_frames(1);
+    assert!(runner.world().resource::<RepairQueue>().contains(house));
+    runner.run_seconds(5.0);
+
+    let world = runner.world();
+    assert!(!world.get::<Health>(house).unwrap().is_damaged());
+    assert!(world.resource::<RepairQueue>().is_empty());
+    assert_eq!(world.resource::<Stockpile>().amount(Good::Wood), wood - 10);
+    assert_eq!(world.query::<&HealthBar>().iter(world).count(), 0);
+
+    world.get_mut::<Health>(house).unwrap().damage(1000);
+    runner.run_frames(3);
+
+    let world = runner.world();
+    assert!(world.get_entity(house).is_none());
+    assert!(
+        !world
+            .resource::<WorldMap>()
+            .get(house_tile)
+            .unwrap()
+            .occupied
+    );
+}
+
+#[test]
+fn unaffordable_repairs_wait_behind_affordable_ones() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let house_tile = start + IVec2::new(4, 0);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.place_building(BuildingType::House, house_tile);
+    runner.run_seconds(UNDO_WINDOW + 1.0);
+
+    let world = runner.world();
+    let health_of = |world: &mut World, tile: IVec2| {
+        *world
+            .query_filtered::<(&TilePosition, &Health), With<Building>>()
+            .iter(world)
+            .find(|(position, _)| position.0 == tile)
+            .expect("building placed")
+            .1
+    };
+    for mut health in world
+        .query_filtered::<&mut Health, With<Building>>()
+        .iter_mut(world)
+    {
+        let half = health.max / 2;
+        health.damage(half);
+    }
+    // The town centre needs stone to repair, the house only wood
+    world.resource_mut::<Stockpile>().set(Good::Stone, 0);
+
+    runner.repair_building(start);
+    runner.run_frames(1);
+    runner.repair_building(house_tile);
+    runner.run_seconds(6.0);
+
+    let world = runner.world();
+    assert!(!health_of(world, house_tile).is_damaged());
+    assert!(health_of(world, start).is_damaged());
+    assert_eq!(world.resource::<RepairQueue>().len(), 1);
+}
+
+#[test]
+fn random_events_start_and_wear_off() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let workers = |runner: &mut HeadlessRunner| {
+        let world = runner.world();
+        world.query::<&Worker>().iter(world).count()
+    };
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    let before = workers(&mut runner);
+
+    let mut director = runner.world().resource_mut::<EventDirector>();
+    director.start(RandomEvent::MigrantWave);
+    director.start(RandomEvent::Drought);
+    director.start(RandomEvent::Fire);
+    runner.run_frames(2);
+
+    assert!(workers(&mut runner) > before);
+    let world = runner.world();
+    assert!(!world.resource::<Wildfire>().is_out());
+    assert!(world
+        .resource::<EventDirector>()
+        .is_active(RandomEvent::Drought));
+    assert!(world
+        .resource::<EventDirector>()
+        .is_active(RandomEvent::Fire));
+
+    runner.run_seconds(4.0);
+    let world = runner.world();
+    let health = world
+        .query_filtered::<&Health, With<TownCentre>>()
+        .single(world);
+    assert!(health.is_damaged());
+
+    runner.run_seconds(2.0 * DAY_SECONDS + 1.0);
+    let director = runner.world().resource::<EventDirector>();
+    assert!(!director.is_active(RandomEvent::Drought));
+    assert!(director.day >= 2);
+}
+
+#[test]
+fn notifications_are_logged_with_where_they_happened() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let log = |runner: &mut HeadlessRunner| {
+        runner
+            .world()
+            .resource::<NotificationLog>()
+            .entries()
+            .iter()
+            .map(|logged| logged.notification.clone())
+            .collect::<Vec<_>>()
+    };
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    let built = log(&mut runner);
+    assert_eq!(built.len(), 1);
+    assert_eq!(built[0].severity, Severity::Good);
+    assert_eq!(built[0].tile, Some(start));
+
+    // Only warned once while food stays short
+    runner
+        .world()
+        .resource_mut::<Stockpile>()
+        .set(Good::Food, 5);
+    runner.run_frames(2);
+    runner
+        .world()
+        .resource_mut::<Stockpile>()
+        .set(Good::Food, 4);
+    runner.run_frames(2);
+
+    let warnings = log(&mut runner)
+        .into_iter()
+        .filter(|notification| notification.severity == Severity::Warning)
+        .count();
+    assert_eq!(warnings, 1);
+
+    runner.world().resource_mut::<Raids>().call_next_wave();
+    runner.run_frames(2);
+    let raid = log(&mut runner).pop().unwrap();
+    assert_eq!(raid.severity, Severity::Danger);
+    assert!(raid.tile.is_some());
+}
+
+#[test]
+fn minimap_covers_the_whole_map_with_north_at_the_top() {
+    let mut runner = runner(7);
+    let map = runner.world().resource::<WorldMap>();
+    let view = MinimapView::new(map, map.start);
+
+    assert_eq!(view.origin, IVec2::ZERO);
+    assert_eq!(view.size, UVec2::new(48, 32));
+    assert_eq!(view.tile_at(Vec2::ZERO), IVec2::new(0, 31));
+    assert_eq!(view.tile_at(Vec2::ONE), IVec2::new(47, 0));
+    assert_eq!(view.tile_at(Vec2::new(0.5, 0.5)), IVec2::new(24, 16));
+
+    // Clicking where a tile is drawn picks that tile again
+    let tile = IVec2::new(10, 20);
+    assert_eq!(view.tile_at(view.point_of(tile.as_vec2())), tile);
+}
+
+#[test]
+fn objectives_are_completed_in_order_until_the_town_centre_falls() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    let objectives = runner.world().resource::<Objectives>();
+    assert_eq!(objectives.current(), Some(Objective::ORDER[0]));
+    assert_eq!(objectives.progress(), 150);
+
+    runner
+        .world()
+        .resource_mut::<Stockpile>()
+        .set(Good::Wood, 200);
+    runner.run_frames(2);
+    let world = runner.world();
+    let population = world.query::<&Worker>().iter(world).count() as u32;
+    let objectives = world.resource::<Objectives>();
+    assert_eq!(objectives.completed(), 1);
+    assert_eq!(objectives.current(), Some(Objective::Population(20)));
+    assert_eq!(objectives.progress(), population);
+    assert!(!runner.is_game_over());
+
+    let world = runner.world();
+    world
+        .query_filtered::<&mut Health, With<TownCentre>>()
+        .single_mut(world)
+        .damage(10_000);
+    runner.run_frames(3);
+
+    assert!(runner.is_game_over());
+    assert_eq!(
+        runner.world().resource::<Objectives>().outcome(),
+        Some(Outcome::TownCentreDestroyed)
+    );
+}
+
+#[test]
+fn the_game_is_lost_once_everyone_has_starved() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    let world = runner.world();
+    world.resource_mut::<Stockpile>().set(Good::Food, 0);
+    // One missed meal away from starving
+    for mut health in world
+        .query_filtered::<&mut Health, With<Worker>>()
+        .iter_mut(world)
+    {
+        health.current = 10;
+    }
+
+    runner.run_seconds(25.0);
+    assert!(!runner.is_game_over());
+
+    runner.run_seconds(10.0);
+    assert!(runner.is_game_over());
+    assert_eq!(
+        runner.world().resource::<Objectives>().outcome(),
+        Some(Outcome::Starved)
+    );
+}
+
+#[test]
+fn research_unlocks_buildings_along_the_tree() {
+    let mut runner = runner(7);
+    let start = runner.world().resource::<StartLocation>().tile;
+    let mill = start + IVec2::new(2, 0);
+
+    runner.place_building(BuildingType::TownCentre, start);
+    runner.run_frames(2);
+    runner.place_building(BuildingType::Road, start + IVec2::new(1, 0));
+    runner.place_building(BuildingType::LumberMill, mill);
+    runner.run_frames(2);
+    assert!(!buildings(&mut runner).contains(&(BuildingType::LumberMill, mill)));
+
+    // Masonry needs forestry first
+    runner.start_research(Tech::Masonry);
+    runner.run_frames(1);
+    assert_eq!(runner.world().resource::<Research>().current(), None);
+
+    let wood = runner.world().resource::<Stockpile>().amount(Good::Wood);
+    runner.start_research(Tech::Forestry);
+    runner.start_research(Tech::Trade);
+    runner.run_frames(1);
+    let world = runner.world();
+    assert_eq!(world.resource::<Research>().current(), Some(Tech::Forestry));
+    assert_eq!(world.resource::<Stockpile>().amount(Good::Wood), wood - 30);
+
+    runner.run_seconds(Tech::Forestry.seconds() + 1.0);
+    let research = runner.world().resource::<Research>();
+    assert!(research.is_researched(Tech::Forestry));
+    assert!(research.is_available(Tech::Masonry));
+    assert!(research.is_unlocked(BuildingType::LumberMill));
+    assert!(!research.is_unlocked(BuildingType::Stonecutter));
+
+    runner.place_building(BuildingType::LumberMill, mill);
+    runner.run_frames(2);
+    assert!(buildings(&mut runner).contains(&(BuildingType::LumberMill, mill)));
+}
+
+fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+    line.split_whitespace().map(str::to_string)
+}
+
+#[test]
+fn parses_launch_flags() {
+    let (global_state, options) = LaunchOptions::from_args(args(
+        "--seed 42 --width 20 --height 10 --block-size 32 --skip-menu",
+    ))
+    .unwrap();
+
+    assert_eq!(global_state.seed, 42);
+    assert_eq!((global_state.world_cols, global_state.world_rows), (20, 10));
+    assert_eq!(global_state.block_size, 32);
+    assert_eq!(options.seed, Some(42));
+    assert!(options.skip_menu);
+    assert!(options.load.is_none());
+
+    for (line, error) in [
+        ("--seed forty", "--seed expects a number, got forty"),
+        (
+            "--seed 4294967296",
+            "--seed expects a number, got 4294967296",
+        ),
+        ("--width", "--width is missing a value"),
+        ("--width 0", "the map needs at least one column and one row"),
+        (
+            "--block-size 0",
+            "the block size has to be at least one pixel",
+        ),
+        ("--fullscreen", "unknown option --fullscreen"),
+    ] {
+        assert_eq!(LaunchOptions::from_args(args(line)).err().unwrap(), error);
+    }
+}
+
+#[test]
+fn a_save_brings_its_own_world_settings() {
+    let path = std::env::temp_dir().join(format!("game_growth_cli_{}.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        "seed 42\ncols 20\nrows 10\nbuilding TownCentre 10 5\n",
+    )
+    .unwrap();
+    let load = format!("--load {}", path.display());
+
+    let (global_state, options) = LaunchOptions::from_args(args(&load)).unwrap();
+    assert_eq!(global_state.seed, 42);
+    assert_eq!((global_state.world_cols, global_state.world_rows), (20, 10));
+    assert!(options.skip_menu);
+    assert_eq!(options.load.unwrap().1.buildings.len(), 1);
+
+    for flag in ["--seed 7", "--width 30", "--height 30", "--block-size 32"] {
+        let error = LaunchOptions::from_args(args(&format!("{} {}", flag, load)))
+            .err()
+            .unwrap();
+        assert!(error.starts_with(flag.split(' ').next().unwrap()));
+    }
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(LaunchOptions::from_args(args(&load))
+        .err()
+        .unwrap()
+        .starts_with("could not read"));
+}
+
+#[test]
+fn parses_save_files() {
+    let text = "seed 42\ncols 64\nrows 32\nblock_size 64\nresources normal\nwater normal\n\
+        world fixed\nstock wood 130\nresearch forestry\nbuilding TownCentre 31 15 north\n";
+    let save = SaveGame::parse(text).unwrap();
+
+    assert_eq!(save.global_state.seed, 42);
+    assert_eq!(save.stockpile, vec![(Good::Wood, 130)]);
+    assert_eq!(save.research, vec![Tech::Forestry]);
+    assert_eq!(
+        save.buildings,
+        vec![(BuildingType::TownCentre, IVec2::new(31, 15), Facing::North)]
+    );
+    assert_eq!(save.to_text(), text);
+
+    // Saves from before buildings could turn face north
+    let save = SaveGame::parse("building House 1 2").unwrap();
+    assert_eq!(save.buildings[0].2, Facing::North);
+
+    for (text, error) in [
+        ("seed 42\ncols many", "line 2: cols expects a number"),
+        ("stock wood", "line 1: stock is missing a value"),
+        ("colour red", "line 1: unknown key colour"),
+    ] {
+        assert_eq!(SaveGame::parse(text).err().unwrap(), error);
+    }
+}